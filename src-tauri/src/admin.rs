@@ -0,0 +1,135 @@
+use axum::{
+    body::Bytes,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::sync::broadcast;
+
+use crate::db::Database;
+use crate::journal::Journal;
+use crate::models::{Route, UpdateCollectionRequest, UpdateRouteRequest};
+use crate::request::RequestContext;
+use crate::websocket::WebSocketBroadcast;
+
+/// URL prefix of the admin API on mock servers whose collection turns it on.
+pub const ADMIN_PREFIX: &str = "/__mocify";
+
+#[derive(Deserialize)]
+struct PushRequest {
+    path: Option<String>,
+    message: String,
+}
+
+/// Serves the admin API, which exposes the app's collection and route commands over
+/// HTTP/JSON for test suites that can't call Tauri commands. Paths are relative to
+/// `ADMIN_PREFIX`:
+///
+/// - `GET /collection`, `PATCH /collection`: read or change the collection's settings
+/// - `GET /routes`, `POST /routes`: list or create routes
+/// - `GET /routes/{id}`, `PATCH /routes/{id}`, `DELETE /routes/{id}`
+/// - `GET /requests`, `DELETE /requests`: the request journal
+/// - `POST /reset`: forget recorded requests
+/// - `POST /websocket/push`: push `{ "path", "message" }` to WebSocket clients
+///
+/// Bodies must be sent as `application/json`. Browsers only send that cross-origin after
+/// a CORS preflight, which the admin API never approves, so web pages can't use it.
+pub async fn handle(
+    db: &Database,
+    collection_id: &str,
+    journal: &Journal,
+    websocket_tx: &broadcast::Sender<WebSocketBroadcast>,
+    request: &RequestContext,
+) -> Response {
+    let RequestContext { method, headers, body, .. } = request;
+    if !body.is_empty() && !is_json(headers) {
+        return error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Admin requests must be sent as application/json");
+    }
+
+    let path = request.path.strip_prefix(ADMIN_PREFIX).unwrap_or_default();
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let result = match (method.as_str(), segments.as_slice()) {
+        ("GET", ["collection"]) => db.get_collection(collection_id).await.map(|c| json_response(StatusCode::OK, c)),
+        ("PATCH", ["collection"]) => match parse_with::<UpdateCollectionRequest>(body, "id", collection_id) {
+            Ok(request) => db.update_collection(request).await.map(|c| json_response(StatusCode::OK, c)),
+            Err(e) => return error(StatusCode::BAD_REQUEST, &e),
+        },
+        ("GET", ["routes"]) => db.get_routes(collection_id).await.map(|r| json_response(StatusCode::OK, r)),
+        ("POST", ["routes"]) => match parse_with(body, "collection_id", collection_id) {
+            Ok(request) => db.create_route(request).await.map(|r| json_response(StatusCode::CREATED, r)),
+            Err(e) => return error(StatusCode::BAD_REQUEST, &e),
+        },
+        (_, ["routes", id]) => {
+            let route = match find_route(db, collection_id, id).await {
+                Ok(Some(route)) => route,
+                Ok(None) => return error(StatusCode::NOT_FOUND, "Route not found"),
+                Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+            };
+
+            match method.as_str() {
+                "GET" => Ok(json_response(StatusCode::OK, route)),
+                "PATCH" | "PUT" => match parse_with::<UpdateRouteRequest>(body, "id", id) {
+                    Ok(request) => db.update_route(request).await.map(|r| json_response(StatusCode::OK, r)),
+                    Err(e) => return error(StatusCode::BAD_REQUEST, &e),
+                },
+                "DELETE" => db.delete_route(id).await.map(|_| StatusCode::NO_CONTENT.into_response()),
+                _ => return error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"),
+            }
+        }
+        ("GET", ["requests"]) => Ok(json_response(StatusCode::OK, journal.entries())),
+        ("DELETE", ["requests"]) | ("POST", ["reset"]) => {
+            journal.clear();
+            Ok(StatusCode::NO_CONTENT.into_response())
+        }
+        ("POST", ["websocket", "push"]) => {
+            let request: PushRequest = match parse(body) {
+                Ok(request) => request,
+                Err(e) => return error(StatusCode::BAD_REQUEST, &e),
+            };
+            let _ = websocket_tx.send(WebSocketBroadcast::Message {
+                path: request.path,
+                message: request.message,
+            });
+            Ok(StatusCode::NO_CONTENT.into_response())
+        }
+        _ => return error(StatusCode::NOT_FOUND, "Unknown admin endpoint"),
+    };
+
+    result.unwrap_or_else(|e| error(StatusCode::BAD_REQUEST, &e.to_string()))
+}
+
+/// Looks up a route, treating routes of other collections as missing.
+async fn find_route(db: &Database, collection_id: &str, id: &str) -> anyhow::Result<Option<Route>> {
+    Ok(db.get_route(id).await?.filter(|route| route.collection_id == collection_id))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case("application/json"))
+}
+
+fn parse<T: DeserializeOwned>(body: &Bytes) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+/// Parses a JSON object body after setting `key`, for requests whose ID comes from the URL.
+fn parse_with<T: DeserializeOwned>(body: &Bytes, key: &str, value: &str) -> Result<T, String> {
+    let mut object: Map<String, Value> = parse(body)?;
+    object.insert(key.to_string(), Value::String(value.to_string()));
+    serde_json::from_value(Value::Object(object)).map_err(|e| format!("Invalid request: {}", e))
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: T) -> Response {
+    (status, Json(value)).into_response()
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
@@ -2,7 +2,7 @@ use tauri::State;
 use std::time::Instant;
 use log::{info, debug, error};
 
-use crate::{AppState, models::*, mock_server::MockServer};
+use crate::{AppState, contract, fake::Faker, grpc, matching, models::*, mock_server::{self, MockServer}, oidc, rate_limit::RateLimitCounter, session::Session};
 
 // Collection commands
 #[tauri::command]
@@ -28,6 +28,47 @@ pub async fn create_collection(
     result.map_err(|e| e.to_string())
 }
 
+/// Creates a collection from the OpenID Connect provider template: a fresh signing
+/// key, a confidential and a public client, and a demo user.
+#[tauri::command]
+pub async fn create_oidc_collection(
+    state: State<'_, AppState>,
+    name: String,
+    port: u16,
+) -> Result<Collection, String> {
+    // RSA key generation is slow enough to keep off the async runtime
+    let oidc = tokio::task::spawn_blocking(oidc::template)
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let request = CreateCollectionRequest {
+        name,
+        description: Some("Mock OpenID Connect provider".to_string()),
+        port,
+        base_path: None,
+        bandwidth_bytes_per_sec: None,
+        asset_dir: None,
+        static_mounts: Vec::new(),
+        kind: CollectionKind::Http,
+        proto_files: Vec::new(),
+        openapi_spec: None,
+        contract_check: false,
+        admin_api: false,
+        fallback: None,
+        path_normalization: PathNormalization::default(),
+        auth: None,
+        oidc: Some(oidc),
+        cors: CorsConfig::default(),
+        rate_limit: None,
+        session: SessionConfig::default(),
+        compression: None,
+    };
+
+    state.db.create_collection(request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_collections(
     state: State<'_, AppState>,
@@ -42,9 +83,24 @@ pub async fn update_collection(
     state: State<'_, AppState>,
     request: UpdateCollectionRequest,
 ) -> Result<Collection, String> {
-    state.db.update_collection(request)
+    let previous = state.db.get_collection(&request.id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?
+        .ok_or("Collection not found")?;
+
+    let collection = state.db.update_collection(request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // The admin API is mounted when the server starts, so a running server restarts to
+    // add or drop it
+    if collection.admin_api != previous.admin_api {
+        if let Some(server) = state.servers.lock().await.get_mut(&previous.port) {
+            server.restart(state.db.clone()).await?;
+        }
+    }
+
+    Ok(collection)
 }
 
 #[tauri::command]
@@ -106,6 +162,135 @@ pub async fn delete_route(
         .map_err(|e| e.to_string())
 }
 
+/// Creates a route for every RPC in a gRPC collection's `.proto` files that doesn't
+/// have one yet, returning the new routes.
+#[tauri::command]
+pub async fn import_proto_services(
+    state: State<'_, AppState>,
+    collection_id: String,
+) -> Result<Vec<Route>, String> {
+    let collection = state.db.get_collection(&collection_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Collection not found")?;
+
+    let pool = grpc::load_descriptors(&collection)?;
+    let existing = state.db.get_routes(&collection_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut created = Vec::new();
+    for path in grpc::rpc_paths(&pool) {
+        if existing.iter().any(|route| route.path == path) {
+            continue;
+        }
+
+        let route = state.db.create_route(CreateRouteRequest {
+            collection_id: collection_id.clone(),
+            name: path.trim_start_matches('/').to_string(),
+            kind: RouteKind::Http,
+            method: HttpMethod::Post,
+            path,
+            path_match: PathMatch::Exact,
+            status_code: 200,
+            response_body: None,
+            body_source: BodySource::Inline,
+            representations: Vec::new(),
+            sse: None,
+            websocket: None,
+            graphql: None,
+            grpc: Some(GrpcResponse {
+                messages: vec![serde_json::json!({})],
+                ..Default::default()
+            }),
+            validation: None,
+            template: false,
+            fake_seed: None,
+            request_match: None,
+            priority: 0,
+            auth: None,
+            rate_limit: None,
+            cookies: Vec::new(),
+            session: None,
+            response_headers: ResponseHeaders::default(),
+            delay_ms: None,
+            bandwidth_bytes_per_sec: None,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+        created.push(route);
+    }
+
+    Ok(created)
+}
+
+/// Checks every HTTP route's status, headers and body against the collection's OpenAPI document.
+#[tauri::command]
+pub async fn check_contract(
+    state: State<'_, AppState>,
+    collection_id: String,
+) -> Result<Vec<ContractReport>, String> {
+    let collection = state.db.get_collection(&collection_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Collection not found")?;
+
+    let spec = contract::parse_spec(collection.openapi_spec.as_deref().ok_or("Collection has no OpenAPI document")?)?;
+    let routes = state.db.get_routes(&collection_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut reports = Vec::new();
+    for route in routes.into_iter().filter(|route| route.kind == RouteKind::Http) {
+        let violations = if route.representations.is_empty() {
+            match mock_server::load_body(&route, &collection).await {
+                Ok((body, file_name)) => {
+                    let headers = mock_server::response_headers(&route, &body, file_name.as_deref());
+                    contract::check_response(&spec, route.method.as_str(), &route.path, route.status_code, &headers, &body)
+                }
+                Err(e) => vec![e],
+            }
+        } else {
+            // Each representation is a response the route can send, so each has to conform
+            route.representations.iter().flat_map(|representation| {
+                let body = axum::body::Bytes::from(representation.body.clone());
+                let headers = mock_server::representation_headers(&route, representation, &body);
+                contract::check_response(&spec, route.method.as_str(), &route.path, route.status_code, &headers, &body)
+            }).collect()
+        };
+
+        reports.push(ContractReport {
+            route_id: route.id,
+            route_name: route.name,
+            method: route.method.as_str().to_string(),
+            path: route.path,
+            violations,
+        });
+    }
+
+    Ok(reports)
+}
+
+/// Replaces a route's body with JSON generated from a schema.
+#[tauri::command]
+pub async fn generate_route_body(
+    state: State<'_, AppState>,
+    request: GenerateBodyRequest,
+) -> Result<Route, String> {
+    let body = Faker::new(request.seed).value_for_schema(&request.schema)?;
+    let body = serde_json::to_string_pretty(&body).map_err(|e| e.to_string())?;
+
+    state.db.update_route(UpdateRouteRequest {
+        id: request.route_id,
+        response_body: Some(body),
+        body_source: Some(BodySource::Inline),
+        ..Default::default()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 // Server commands
 #[tauri::command]
 pub async fn start_server(
@@ -178,6 +363,65 @@ pub async fn get_running_servers(
     Ok(server_statuses)
 }
 
+#[tauri::command]
+pub async fn push_websocket_message(
+    state: State<'_, AppState>,
+    request: PushWebSocketMessageRequest,
+) -> Result<(), String> {
+    let servers = state.servers.lock().await;
+    let server = servers.get(&request.port).ok_or("Server not found")?;
+
+    server.push_websocket_message(request.path, request.message);
+    Ok(())
+}
+
+/// Lists where each client of a running server stands against its rate limits.
+#[tauri::command]
+pub async fn get_rate_limits(
+    state: State<'_, AppState>,
+    port: u16,
+) -> Result<Vec<RateLimitCounter>, String> {
+    let servers = state.servers.lock().await;
+    let server = servers.get(&port).ok_or("Server not found")?;
+
+    Ok(server.rate_limits())
+}
+
+#[tauri::command]
+pub async fn reset_rate_limits(
+    state: State<'_, AppState>,
+    port: u16,
+) -> Result<(), String> {
+    let servers = state.servers.lock().await;
+    let server = servers.get(&port).ok_or("Server not found")?;
+
+    server.reset_rate_limits();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_sessions(
+    state: State<'_, AppState>,
+    port: u16,
+) -> Result<Vec<Session>, String> {
+    let servers = state.servers.lock().await;
+    let server = servers.get(&port).ok_or("Server not found")?;
+
+    Ok(server.sessions())
+}
+
+#[tauri::command]
+pub async fn clear_sessions(
+    state: State<'_, AppState>,
+    port: u16,
+) -> Result<(), String> {
+    let servers = state.servers.lock().await;
+    let server = servers.get(&port).ok_or("Server not found")?;
+
+    server.clear_sessions();
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn test_route(
     state: State<'_, AppState>,
@@ -200,21 +444,19 @@ pub async fn test_route(
     }
     drop(servers);
     
-    // Make HTTP request to test the route
-    let url = format!("http://localhost:{}{}", collection.port, route.path);
+    // Make HTTP request to test the route, normalizing its path the way the mock server will
+    let path = matching::sample_path(route.path_match, &route.path)?;
+    let path = matching::normalize_path(&collection.path_normalization, &path);
+    let url = format!("http://localhost:{}{}", collection.port, path);
     let client = reqwest::Client::new();
     
     let start = Instant::now();
     
-    let request_builder = match route.method {
-        HttpMethod::Get => client.get(&url),
-        HttpMethod::Post => client.post(&url),
-        HttpMethod::Put => client.put(&url),
-        HttpMethod::Delete => client.delete(&url),
-        HttpMethod::Patch => client.patch(&url),
-        HttpMethod::Head => client.head(&url),
-        HttpMethod::Options => client.request(reqwest::Method::OPTIONS, &url),
+    let method = match &route.method {
+        HttpMethod::Any => reqwest::Method::GET,
+        method => reqwest::Method::from_bytes(method.as_str().as_bytes()).map_err(|e| e.to_string())?,
     };
+    let request_builder = client.request(method, &url);
     
     let response = request_builder
         .send()
@@ -0,0 +1,169 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde_json::{json, Map, Value};
+
+use crate::models::{ApiKeyLocation, AuthConfig, JwtAuth};
+
+const DEFAULT_REALM: &str = "mocify";
+
+/// Checks a request's credentials, returning the 401 or 403 to send when they fall short.
+pub fn check(config: &AuthConfig, headers: &HeaderMap, query: &Map<String, Value>) -> Option<Response> {
+    match config {
+        AuthConfig::None => None,
+        AuthConfig::ApiKey { location, name, keys } => {
+            let key = match location {
+                ApiKeyLocation::Header => headers.get(name.as_str()).and_then(|value| value.to_str().ok()),
+                ApiKeyLocation::Query => query.get(name).and_then(Value::as_str),
+            };
+            match key {
+                None => Some(reject(StatusCode::UNAUTHORIZED, None, &format!("Missing API key {}", name))),
+                Some(key) if keys.iter().any(|k| k == key) => None,
+                Some(_) => Some(reject(StatusCode::FORBIDDEN, None, "Invalid API key")),
+            }
+        }
+        AuthConfig::Basic { users, realm } => {
+            let challenge = format!("Basic realm=\"{}\"", realm.as_deref().unwrap_or(DEFAULT_REALM));
+            let credentials = authorization(headers, "Basic")
+                .and_then(|encoded| STANDARD.decode(encoded).ok())
+                .and_then(|decoded| String::from_utf8(decoded).ok());
+
+            let Some(credentials) = credentials else {
+                return Some(reject(StatusCode::UNAUTHORIZED, Some(&challenge), "Missing Basic credentials"));
+            };
+            let (username, password) = credentials.split_once(':').unwrap_or((&credentials, ""));
+            if users.iter().any(|user| user.username == username && user.password == password) {
+                None
+            } else {
+                Some(reject(StatusCode::UNAUTHORIZED, Some(&challenge), "Invalid username or password"))
+            }
+        }
+        AuthConfig::Bearer { tokens } => match authorization(headers, "Bearer") {
+            None => Some(missing_bearer()),
+            Some(token) if tokens.iter().any(|t| t == token) => None,
+            Some(_) => Some(invalid_token("The access token is not valid")),
+        },
+        AuthConfig::Jwt(jwt) => match authorization(headers, "Bearer") {
+            None => Some(missing_bearer()),
+            Some(token) => check_jwt(jwt, token),
+        },
+    }
+}
+
+/// Rejects auth configuration that could never accept a request, such as a JWT key
+/// that doesn't parse for its algorithm.
+pub fn check_config(config: &AuthConfig) -> Result<(), String> {
+    match config {
+        AuthConfig::Jwt(jwt) => decoding_key(jwt).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn check_jwt(jwt: &JwtAuth, token: &str) -> Option<Response> {
+    let key = match decoding_key(jwt) {
+        Ok(key) => key,
+        Err(e) => return Some((StatusCode::INTERNAL_SERVER_ERROR, e).into_response()),
+    };
+
+    let mut validation = Validation::new(jwt.algorithm);
+    // Only check `exp` when the token has one; mocks often deal in tokens that never expire
+    validation.required_spec_claims.clear();
+    validation.validate_aud = jwt.audience.is_some();
+    if let Some(ref issuer) = jwt.issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    if let Some(ref audience) = jwt.audience {
+        validation.set_audience(&[audience]);
+    }
+
+    let claims = match jsonwebtoken::decode::<Map<String, Value>>(token, &key, &validation) {
+        Ok(data) => data.claims,
+        Err(e) => {
+            let description = match e.kind() {
+                ErrorKind::ExpiredSignature => "The token has expired",
+                ErrorKind::ImmatureSignature => "The token is not valid yet",
+                ErrorKind::InvalidIssuer => "The token has the wrong issuer",
+                ErrorKind::InvalidAudience => "The token has the wrong audience",
+                ErrorKind::InvalidSignature => "The token's signature does not verify",
+                ErrorKind::InvalidAlgorithm => "The token is signed with the wrong algorithm",
+                _ => "The token is malformed",
+            };
+            return Some(invalid_token(description));
+        }
+    };
+
+    let missing: Vec<&str> = jwt
+        .required_claims
+        .iter()
+        .filter(|(name, expected)| !claims.get(*name).is_some_and(|actual| claim_matches(actual, expected)))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let description = format!("The token lacks the required claims: {}", missing.join(", "));
+    let challenge = format!(
+        "Bearer realm=\"{}\", error=\"insufficient_scope\", error_description=\"{}\"",
+        DEFAULT_REALM, description
+    );
+    Some(reject(StatusCode::FORBIDDEN, Some(&challenge), &description))
+}
+
+fn decoding_key(jwt: &JwtAuth) -> Result<DecodingKey, String> {
+    let key = jwt.key.as_bytes();
+    let result = match jwt.algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(DecodingKey::from_secret(key)),
+        Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 | Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 => {
+            DecodingKey::from_rsa_pem(key)
+        }
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(key),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(key),
+    };
+    result.map_err(|e| format!("Invalid JWT key for {:?}: {}", jwt.algorithm, e))
+}
+
+fn claim_matches(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        _ if actual == expected => true,
+        (Value::Array(items), _) => items.contains(expected),
+        (Value::String(actual), Value::String(expected)) => {
+            let words: Vec<&str> = actual.split_whitespace().collect();
+            expected.split_whitespace().all(|word| words.contains(&word))
+        }
+        _ => false,
+    }
+}
+
+/// The credentials of an `Authorization: <scheme> <credentials>` header.
+fn authorization<'a>(headers: &'a HeaderMap, scheme: &str) -> Option<&'a str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (given, credentials) = value.split_once(' ')?;
+    given.eq_ignore_ascii_case(scheme).then(|| credentials.trim())
+}
+
+fn missing_bearer() -> Response {
+    let challenge = format!("Bearer realm=\"{}\"", DEFAULT_REALM);
+    reject(StatusCode::UNAUTHORIZED, Some(&challenge), "Missing Bearer token")
+}
+
+fn invalid_token(description: &str) -> Response {
+    let challenge = format!(
+        "Bearer realm=\"{}\", error=\"invalid_token\", error_description=\"{}\"",
+        DEFAULT_REALM, description
+    );
+    reject(StatusCode::UNAUTHORIZED, Some(&challenge), description)
+}
+
+fn reject(status: StatusCode, challenge: Option<&str>, message: &str) -> Response {
+    let error = if status == StatusCode::FORBIDDEN { "forbidden" } else { "unauthorized" };
+    let mut response = (status, Json(json!({ "error": error, "message": message }))).into_response();
+    if let Some(challenge) = challenge.and_then(|c| HeaderValue::from_str(c).ok()) {
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, challenge);
+    }
+    response
+}
@@ -0,0 +1,105 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware,
+    response::Response,
+    Router,
+};
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+use crate::models::{CompressionConfig, ContentEncoding};
+
+/// tower-http's own threshold: below this, compression overhead outweighs the savings.
+const DEFAULT_MIN_SIZE: u16 = 32;
+
+/// The `Accept-Encoding` a request arrived with, put aside while a forced encoding
+/// stands in for it.
+#[derive(Clone)]
+struct OriginalAcceptEncoding(Option<HeaderValue>);
+
+/// Wraps a mock server's router in the compression `config` describes.
+///
+/// Forcing an encoding works by showing the compression layer a request that accepts
+/// only that encoding; the original header is restored before the request reaches the
+/// handler, so routes and the journal see what the client sent.
+pub fn apply<S>(app: Router<S>, config: &CompressionConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let allowed = |encoding| match config.force {
+        Some(forced) => forced == encoding,
+        None => config.encodings.is_empty() || config.encodings.contains(&encoding),
+    };
+    let predicate = SizeAbove::new(config.min_size_bytes.unwrap_or(DEFAULT_MIN_SIZE))
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    let compression = CompressionLayer::new()
+        .gzip(allowed(ContentEncoding::Gzip))
+        .deflate(allowed(ContentEncoding::Deflate))
+        .br(allowed(ContentEncoding::Br))
+        .zstd(allowed(ContentEncoding::Zstd))
+        .compress_when(predicate);
+
+    // Layers added later wrap those added earlier, so this reads from the handler outwards
+    let mut app = app;
+    if config.force.is_some() {
+        app = app.layer(middleware::map_request(restore_accept_encoding));
+    }
+    app = app.layer(compression);
+    if let Some(forced) = config.force {
+        app = app.layer(middleware::map_request(move |request| force_accept_encoding(forced, request)));
+    }
+    if let Some(label) = config.mislabel_as.clone() {
+        app = app.layer(middleware::map_response(move |response| mislabel(label.clone(), response)));
+    }
+    app
+}
+
+/// Rejects a `mislabel_as` that can't be sent as a header.
+pub fn check_config(config: &CompressionConfig) -> Result<(), String> {
+    match config.mislabel_as {
+        Some(ref label) if HeaderValue::from_str(label).is_err() => Err(format!("Invalid Content-Encoding: {:?}", label)),
+        _ => Ok(()),
+    }
+}
+
+async fn force_accept_encoding(encoding: ContentEncoding, mut request: Request) -> Request {
+    let original = request.headers_mut().insert(header::ACCEPT_ENCODING, HeaderValue::from_static(name(encoding)));
+    request.extensions_mut().insert(OriginalAcceptEncoding(original));
+    request
+}
+
+async fn restore_accept_encoding(mut request: Request) -> Request {
+    if let Some(OriginalAcceptEncoding(original)) = request.extensions_mut().remove() {
+        match original {
+            Some(value) => request.headers_mut().insert(header::ACCEPT_ENCODING, value),
+            None => request.headers_mut().remove(header::ACCEPT_ENCODING),
+        };
+    }
+    request
+}
+
+async fn mislabel(label: String, mut response: Response) -> Response {
+    match HeaderValue::from_str(&label) {
+        Ok(value) if !label.is_empty() => {
+            response.headers_mut().insert(header::CONTENT_ENCODING, value);
+        }
+        _ => {
+            response.headers_mut().remove(header::CONTENT_ENCODING);
+        }
+    }
+    response
+}
+
+fn name(encoding: ContentEncoding) -> &'static str {
+    match encoding {
+        ContentEncoding::Gzip => "gzip",
+        ContentEncoding::Deflate => "deflate",
+        ContentEncoding::Br => "br",
+        ContentEncoding::Zstd => "zstd",
+    }
+}
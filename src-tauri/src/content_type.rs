@@ -0,0 +1,149 @@
+use crate::models::{self, Representation};
+
+/// Picks a `Content-Type` for a response body when the route doesn't set one.
+///
+/// A file name, when known, wins via its extension; otherwise the bytes themselves
+/// are sniffed for common binary signatures and text formats.
+pub fn detect(body: &[u8], file_name: Option<&str>) -> Option<String> {
+    if let Some(mime) = file_name.and_then(|name| mime_guess::from_path(name).first()) {
+        return Some(mime.essence_str().to_string());
+    }
+
+    if body.is_empty() {
+        return None;
+    }
+
+    if let Some(mime) = sniff_binary(body) {
+        return Some(mime.to_string());
+    }
+
+    let Ok(text) = std::str::from_utf8(body) else {
+        return Some("application/octet-stream".to_string());
+    };
+
+    let trimmed = text.trim_start();
+    let prefix = trimmed.get(..5).unwrap_or_default().to_ascii_lowercase();
+    let mime = if serde_json::from_str::<serde_json::Value>(text).is_ok() {
+        "application/json"
+    } else if trimmed.starts_with("<?xml") {
+        "application/xml"
+    } else if prefix == "<!doc" || prefix == "<html" {
+        "text/html; charset=utf-8"
+    } else {
+        "text/plain; charset=utf-8"
+    };
+
+    Some(mime.to_string())
+}
+
+fn sniff_binary(body: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x00asm", "application/wasm"),
+    ];
+
+    if body.len() >= 12 && &body[..4] == b"RIFF" && &body[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| body.starts_with(signature))
+        .map(|(_, mime)| *mime)
+}
+
+/// Picks the representation that best fits an `Accept` header, by q-value and then by
+/// the order representations are listed in. Without an `Accept` header the first one is
+/// chosen; `None` means the client accepts none of them.
+pub fn negotiate<'a>(accept: Option<&str>, representations: &'a [Representation]) -> Option<&'a Representation> {
+    let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+        return representations.first();
+    };
+    let ranges: Vec<MediaRange> = accept.split(',').filter_map(MediaRange::parse).collect();
+
+    let mut best: Option<(&Representation, f32)> = None;
+    for representation in representations {
+        let quality = quality(&ranges, &essence(&representation.media_type));
+        if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((representation, quality));
+        }
+    }
+    best.map(|(representation, _)| representation)
+}
+
+/// One entry of an `Accept` header, such as `text/*;q=0.5`.
+struct MediaRange {
+    type_: String,
+    subtype: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    fn parse(range: &str) -> Option<Self> {
+        let mut parts = range.split(';');
+        let (type_, subtype) = parts.next()?.trim().split_once('/')?;
+        let quality = parts
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .map_or(Some(1.0), |(_, value)| value.trim().parse::<f32>().ok())?;
+
+        Some(Self {
+            type_: type_.trim().to_ascii_lowercase(),
+            subtype: subtype.trim().to_ascii_lowercase(),
+            quality: quality.clamp(0.0, 1.0),
+        })
+    }
+
+    /// How closely the range names `type_/subtype`: 2 for exactly, 1 for `type/*`, 0 for
+    /// `*/*`, and `None` when it doesn't cover it at all.
+    fn specificity(&self, type_: &str, subtype: &str) -> Option<u8> {
+        match (self.type_.as_str(), self.subtype.as_str()) {
+            ("*", "*") => Some(0),
+            (t, "*") if t == type_ => Some(1),
+            (t, s) if t == type_ && s == subtype => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// The q-value the most specific matching range gives `media_type`; 0 if none matches.
+fn quality(ranges: &[MediaRange], media_type: &str) -> f32 {
+    let (type_, subtype) = media_type.split_once('/').unwrap_or((media_type, ""));
+    ranges
+        .iter()
+        .filter_map(|range| Some((range.specificity(type_, subtype)?, range.quality)))
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, quality)| quality)
+}
+
+/// A media type without its parameters, lowercased: `text/csv; charset=utf-8` is `text/csv`.
+fn essence(media_type: &str) -> String {
+    media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+/// Rejects representations whose media type is a range or malformed, or that repeat
+/// another's media type and so could never be chosen.
+pub fn check_representations(representations: &[Representation]) -> Result<(), String> {
+    let mut seen = Vec::new();
+    for representation in representations {
+        let media_type = essence(&representation.media_type);
+        let valid = media_type.split_once('/').is_some_and(|(type_, subtype)| {
+            [type_, subtype].iter().all(|part| !part.is_empty() && part.bytes().all(models::is_token_byte))
+                && !media_type.contains('*')
+        });
+        if !valid || representation.media_type.chars().any(char::is_control) {
+            return Err(format!("Invalid media type: {}", representation.media_type));
+        }
+        if seen.contains(&media_type) {
+            return Err(format!("More than one representation is {}", media_type));
+        }
+        seen.push(media_type);
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use axum::http::{header, HeaderMap};
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+
+use crate::validation;
+
+/// A document's text and what parsing it gave.
+type ParsedSpec = (String, Result<Arc<Value>, String>);
+
+/// The parsed OpenAPI document of a mock server's collection, kept until the document
+/// changes, so responses aren't checked against a spec parsed afresh each time.
+#[derive(Clone, Default)]
+pub struct SpecCache {
+    spec: Arc<Mutex<Option<ParsedSpec>>>,
+}
+
+impl SpecCache {
+    /// `text` parsed, or the error it failed with, reusing the last result when `text`
+    /// hasn't changed.
+    pub fn get(&self, text: &str) -> Result<Arc<Value>, String> {
+        let mut cached = self.spec.lock().unwrap();
+        match *cached {
+            Some((ref cached_text, ref spec)) if cached_text == text => spec.clone(),
+            _ => {
+                let spec = parse_spec(text).map(Arc::new);
+                *cached = Some((text.to_string(), spec.clone()));
+                spec
+            }
+        }
+    }
+}
+
+/// Parses an OpenAPI document, which may be written as JSON or YAML.
+pub fn parse_spec(text: &str) -> Result<Value, String> {
+    serde_json::from_str(text)
+        .or_else(|_| serde_yaml::from_str(text))
+        .map_err(|e| format!("OpenAPI document is neither valid JSON nor YAML: {}", e))
+}
+
+/// Lists the ways a response differs from what the spec documents for `method path`.
+/// An empty list means the response conforms.
+pub fn check_response(
+    spec: &Value,
+    method: &str,
+    path: &str,
+    status_code: u16,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Vec<String> {
+    let Some((template, path_item)) = find_path(spec, path) else {
+        return vec![format!("{} is not documented in the spec", path)];
+    };
+    let Some(operation) = path_item.get(method.to_ascii_lowercase()) else {
+        return vec![format!("{} {} is not documented in the spec", method, template)];
+    };
+    let Some(response) = find_response(operation, status_code).map(|r| resolve(spec, r)) else {
+        return vec![format!("Status {} is not documented for {} {}", status_code, method, template)];
+    };
+
+    let mut violations = Vec::new();
+    check_headers(spec, response, headers, &mut violations);
+    check_body(spec, response, headers, body, &mut violations);
+    violations
+}
+
+/// Finds the path item whose template (`/users/{id}`) matches `path`, preferring an exact match.
+fn find_path<'a>(spec: &'a Value, path: &str) -> Option<(&'a str, &'a Value)> {
+    let paths = spec.get("paths")?.as_object()?;
+    if let Some((template, item)) = paths.get_key_value(path) {
+        return Some((template.as_str(), item));
+    }
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    paths.iter().find_map(|(template, item)| {
+        let parts: Vec<&str> = template.trim_matches('/').split('/').collect();
+        let matches = parts.len() == segments.len()
+            && parts
+                .iter()
+                .zip(&segments)
+                .all(|(part, segment)| (part.starts_with('{') && part.ends_with('}')) || part == segment);
+        matches.then_some((template.as_str(), item))
+    })
+}
+
+/// Looks up the response for a status code, falling back to its range (`2XX`) and then `default`.
+fn find_response(operation: &Value, status_code: u16) -> Option<&Value> {
+    let responses = operation.get("responses")?.as_object()?;
+    let range = format!("{}XX", status_code / 100);
+
+    responses
+        .get(&status_code.to_string())
+        .or_else(|| responses.iter().find(|(key, _)| key.eq_ignore_ascii_case(&range)).map(|(_, r)| r))
+        .or_else(|| responses.get("default"))
+}
+
+fn check_headers(spec: &Value, response: &Value, headers: &HeaderMap, violations: &mut Vec<String>) {
+    let Some(documented) = response.get("headers").and_then(Value::as_object) else {
+        return;
+    };
+
+    for (name, definition) in documented {
+        let definition = resolve(spec, definition);
+        match headers.get(name.as_str()) {
+            None if definition["required"] == Value::Bool(true) => {
+                violations.push(format!("Missing required header {}", name));
+            }
+            None => {}
+            Some(value) => {
+                let Some(schema) = definition.get("schema") else {
+                    continue;
+                };
+                let value = value.to_str().unwrap_or_default();
+                // Header values are strings on the wire; read them as JSON when the schema wants a number or boolean
+                let instance = match schema.get("type").and_then(Value::as_str) {
+                    Some("integer" | "number" | "boolean") => {
+                        serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+                    }
+                    _ => Value::String(value.to_string()),
+                };
+                for error in schema_errors(spec, schema, &instance) {
+                    violations.push(format!("Header {}: {}", name, error));
+                }
+            }
+        }
+    }
+}
+
+fn check_body(spec: &Value, response: &Value, headers: &HeaderMap, body: &[u8], violations: &mut Vec<String>) {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+
+    // Swagger 2.0 puts the schema straight on the response
+    let schema = match response.get("content").and_then(Value::as_object) {
+        Some(content) => {
+            let media = content_type.as_deref().and_then(|content_type| find_media_type(content, content_type));
+            match media {
+                Some((media_type, media)) if media_type.contains("json") => media.get("schema"),
+                Some(_) => None,
+                None => {
+                    let documented: Vec<&str> = content.keys().map(String::as_str).collect();
+                    violations.push(format!(
+                        "Content-Type {} is not one of the documented media types ({})",
+                        content_type.as_deref().unwrap_or("(none)"),
+                        documented.join(", ")
+                    ));
+                    return;
+                }
+            }
+        }
+        None => response.get("schema"),
+    };
+
+    let Some(schema) = schema else {
+        if response.get("content").is_none() && !body.is_empty() {
+            violations.push("The spec documents no body for this response".to_string());
+        }
+        return;
+    };
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(instance) => {
+            for error in schema_errors(spec, schema, &instance) {
+                violations.push(format!("Body: {}", error));
+            }
+        }
+        Err(e) => violations.push(format!("Body is not valid JSON: {}", e)),
+    }
+}
+
+/// Picks the documented media type for a Content-Type, allowing `type/*` and `*/*` entries.
+fn find_media_type<'a>(content: &'a Map<String, Value>, content_type: &str) -> Option<(&'a str, &'a Value)> {
+    let wildcard = format!("{}/*", content_type.split('/').next().unwrap_or_default());
+
+    [content_type, wildcard.as_str(), "*/*"]
+        .iter()
+        .find_map(|candidate| content.get_key_value(*candidate))
+        .map(|(media_type, media)| (media_type.as_str(), media))
+}
+
+/// Validates against a schema from the spec. The spec's `components` (and Swagger 2.0
+/// `definitions`) are copied into the schema so that local `$ref`s resolve.
+fn schema_errors(spec: &Value, schema: &Value, instance: &Value) -> Vec<String> {
+    let mut schema = schema.clone();
+    if let Value::Object(ref mut map) = schema {
+        for section in ["components", "definitions"] {
+            if let Some(value) = spec.get(section) {
+                map.entry(section).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    match validation::compile_schema(&schema) {
+        Ok(validator) => validator
+            .iter_errors(instance)
+            .map(|error| match error.instance_path.as_str() {
+                "" => error.to_string(),
+                path => format!("{} at {}", error, path),
+            })
+            .collect(),
+        Err(e) => vec![e],
+    }
+}
+
+/// Follows a local `$ref` (`#/components/...`) to the object it points at.
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| spec.pointer(pointer))
+        .unwrap_or(value)
+}
@@ -0,0 +1,86 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use serde_json::{Map, Value};
+
+use crate::models::{self, ResponseCookie, SameSite};
+use crate::template;
+
+/// The cookies a request sent, by name. When a name appears twice the first wins, as
+/// browsers send the most specific cookie first.
+pub fn parse(headers: &HeaderMap) -> Map<String, Value> {
+    let mut cookies = Map::new();
+    let pairs = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='));
+
+    for (name, value) in pairs {
+        let value = value.trim();
+        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+        cookies.entry(name.trim()).or_insert_with(|| Value::String(value.to_string()));
+    }
+    cookies
+}
+
+/// The `Set-Cookie` value for `cookie`, with its value rendered against `context`.
+/// `None` when the rendered value isn't allowed in a header.
+pub fn set_cookie(cookie: &ResponseCookie, context: &Value) -> Option<HeaderValue> {
+    let value = template::render(&cookie.value, context);
+    HeaderValue::from_str(&format_cookie(cookie, &value)).ok()
+}
+
+/// A `Set-Cookie` value telling the browser to forget the cookie `name` on `path`.
+pub fn expire_cookie(name: &str, path: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(&format!("{}=; Path={}; Max-Age=0", name, path)).ok()
+}
+
+fn format_cookie(cookie: &ResponseCookie, value: &str) -> String {
+    let mut text = format!("{}={}", cookie.name, value);
+    if let Some(ref path) = cookie.path {
+        text.push_str(&format!("; Path={}", path));
+    }
+    if let Some(ref domain) = cookie.domain {
+        text.push_str(&format!("; Domain={}", domain));
+    }
+    if let Some(max_age) = cookie.max_age_secs {
+        text.push_str(&format!("; Max-Age={}", max_age));
+    }
+    if cookie.secure {
+        text.push_str("; Secure");
+    }
+    if cookie.http_only {
+        text.push_str("; HttpOnly");
+    }
+    if let Some(same_site) = cookie.same_site {
+        let same_site = match same_site {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        };
+        text.push_str(&format!("; SameSite={}", same_site));
+    }
+    text
+}
+
+/// Rejects cookies a browser would refuse or that can't be written as a header.
+pub fn check_cookie(cookie: &ResponseCookie) -> Result<(), String> {
+    check_name(&cookie.name)?;
+    let attributes = [cookie.path.as_deref(), cookie.domain.as_deref()];
+    if attributes.into_iter().flatten().any(|value| value.contains(';') || value.chars().any(char::is_control)) {
+        return Err(format!("Invalid path or domain for cookie {}", cookie.name));
+    }
+    if cookie.same_site == Some(SameSite::None) && !cookie.secure {
+        return Err(format!("Cookie {} has SameSite=None, which browsers only accept with Secure", cookie.name));
+    }
+    Ok(())
+}
+
+/// Cookie names are HTTP tokens: no spaces, separators or control characters.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if !name.is_empty() && name.bytes().all(models::is_token_byte) {
+        Ok(())
+    } else {
+        Err(format!("Invalid cookie name: {}", name))
+    }
+}
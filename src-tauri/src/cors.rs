@@ -0,0 +1,69 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders};
+
+use crate::models::CorsConfig;
+
+/// Builds the CORS layer for a collection's mock server, or `None` when CORS is disabled
+/// and responses should carry no CORS headers at all.
+///
+/// Empty lists allow anything. Browsers don't accept `*` on credentialed requests, so
+/// with `allow_credentials` the request's own origin, method and headers are echoed back
+/// instead.
+pub fn layer(config: &CorsConfig) -> Result<Option<CorsLayer>, String> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    let credentials = config.allow_credentials;
+
+    let origins = parse_all(&config.allowed_origins, "origin", |origin| HeaderValue::from_str(origin).ok())?;
+    let allow_origin = match origins {
+        origins if !origins.is_empty() => AllowOrigin::list(origins),
+        _ if credentials => AllowOrigin::mirror_request(),
+        _ => AllowOrigin::any(),
+    };
+
+    let methods = parse_all(&config.allowed_methods, "method", |method| Method::from_bytes(method.as_bytes()).ok())?;
+    let allow_methods = match methods {
+        methods if !methods.is_empty() => AllowMethods::list(methods),
+        _ if credentials => AllowMethods::mirror_request(),
+        _ => AllowMethods::any(),
+    };
+
+    let headers = parse_all(&config.allowed_headers, "header", |name| HeaderName::from_bytes(name.as_bytes()).ok())?;
+    let allow_headers = match headers {
+        headers if !headers.is_empty() => AllowHeaders::list(headers),
+        _ if credentials => AllowHeaders::mirror_request(),
+        _ => AllowHeaders::any(),
+    };
+
+    let exposed = parse_all(&config.exposed_headers, "header", |name| HeaderName::from_bytes(name.as_bytes()).ok())?;
+    let expose_headers = match exposed {
+        exposed if !exposed.is_empty() => ExposeHeaders::list(exposed),
+        // There's no way to expose every header to credentialed requests
+        _ if credentials => ExposeHeaders::list([]),
+        _ => ExposeHeaders::any(),
+    };
+
+    let mut layer = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(allow_methods)
+        .allow_headers(allow_headers)
+        .expose_headers(expose_headers)
+        .allow_credentials(credentials);
+    if let Some(max_age) = config.max_age_secs {
+        layer = layer.max_age(Duration::from_secs(max_age));
+    }
+    Ok(Some(layer))
+}
+
+/// Parses each entry of a CORS list. `*` is rejected, as an empty list is how to allow anything.
+fn parse_all<T>(values: &[String], kind: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    values
+        .iter()
+        .map(|value| match value.trim() {
+            "*" => Err(format!("Use an empty list rather than * to allow any {}", kind)),
+            value => parse(value).ok_or_else(|| format!("Invalid CORS {}: {}", kind, value)),
+        })
+        .collect()
+}
@@ -1,9 +1,28 @@
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use crate::auth;
+use crate::compression;
+use crate::content_type;
+use crate::cookies;
+use crate::cors;
+use crate::graphql;
+use crate::matching;
+use crate::mock_server;
 use crate::models::*;
+use crate::oidc;
+use crate::rate_limit;
+use crate::sse;
+use crate::static_files;
+use crate::validation;
+use crate::websocket;
+
+const COLLECTION_COLUMNS: &str = "id, name, description, port, base_path, bandwidth_bytes_per_sec, asset_dir, static_mounts, kind, proto_files, openapi_spec, contract_check, admin_api, fallback_config, path_normalization, auth_config, oidc_config, cors_config, rate_limit, session_config, compression_config, created_at, updated_at";
+
+const ROUTE_COLUMNS: &str = "id, collection_id, name, kind, method, path, path_match, status_code, response_body, body_source, body_file_path, response_blob, representations, sse_config, websocket_config, graphql_config, grpc_config, validation_config, template, fake_seed, request_match, priority, auth_config, rate_limit, cookies, session_update, response_headers, delay_ms, bandwidth_bytes_per_sec, created_at, updated_at";
 
 #[derive(Clone)]
 pub struct Database {
@@ -53,14 +72,92 @@ impl Database {
                 delay_ms INTEGER,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
-                FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE,
-                UNIQUE(collection_id, method, path)
+                FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE
             )
             "#,
         )
         .execute(&self.pool)
         .await?;
 
+        // Columns added after the initial schema
+        self.add_column_if_missing("collections", "bandwidth_bytes_per_sec", "INTEGER").await?;
+        self.add_column_if_missing("routes", "bandwidth_bytes_per_sec", "INTEGER").await?;
+        self.add_column_if_missing("collections", "asset_dir", "TEXT").await?;
+        self.add_column_if_missing("routes", "body_source", "TEXT NOT NULL DEFAULT 'inline'").await?;
+        self.add_column_if_missing("routes", "body_file_path", "TEXT").await?;
+        self.add_column_if_missing("routes", "response_blob", "BLOB").await?;
+        self.add_column_if_missing("collections", "static_mounts", "TEXT").await?;
+        self.add_column_if_missing("routes", "kind", "TEXT NOT NULL DEFAULT 'http'").await?;
+        self.add_column_if_missing("routes", "sse_config", "TEXT").await?;
+        self.add_column_if_missing("routes", "websocket_config", "TEXT").await?;
+        self.add_column_if_missing("routes", "graphql_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "kind", "TEXT NOT NULL DEFAULT 'http'").await?;
+        self.add_column_if_missing("collections", "proto_files", "TEXT").await?;
+        self.add_column_if_missing("routes", "grpc_config", "TEXT").await?;
+        self.add_column_if_missing("routes", "validation_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "openapi_spec", "TEXT").await?;
+        self.add_column_if_missing("collections", "contract_check", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("routes", "template", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("routes", "fake_seed", "INTEGER").await?;
+        self.add_column_if_missing("collections", "admin_api", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("routes", "request_match", "TEXT").await?;
+        self.add_column_if_missing("routes", "priority", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("routes", "path_match", "TEXT NOT NULL DEFAULT 'exact'").await?;
+        self.add_column_if_missing("collections", "fallback_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "path_normalization", "TEXT").await?;
+        self.add_column_if_missing("routes", "auth_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "auth_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "oidc_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "cors_config", "TEXT").await?;
+        self.add_column_if_missing("collections", "rate_limit", "TEXT").await?;
+        self.add_column_if_missing("routes", "rate_limit", "TEXT").await?;
+        self.add_column_if_missing("collections", "session_config", "TEXT").await?;
+        self.add_column_if_missing("routes", "cookies", "TEXT").await?;
+        self.add_column_if_missing("routes", "session_update", "TEXT").await?;
+        self.add_column_if_missing("routes", "representations", "TEXT").await?;
+        self.add_column_if_missing("collections", "compression_config", "TEXT").await?;
+
+        // Routes sharing a method and path are told apart by `request_match`
+        self.drop_route_unique_constraint().await?;
+
+        Ok(())
+    }
+
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let columns = sqlx::query_as::<_, (String,)>(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(&self.pool)
+            .await?;
+
+        if !columns.iter().any(|(name,)| name == column) {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds `routes` without the `UNIQUE(collection_id, method, path)` constraint of
+    /// older databases, since SQLite can't drop a table constraint in place.
+    async fn drop_route_unique_constraint(&self) -> Result<()> {
+        let (sql,) = sqlx::query_as::<_, (String,)>("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'routes'")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let Some(start) = sql.find("UNIQUE(collection_id, method, path)") else {
+            return Ok(());
+        };
+        let separator = sql[..start].rfind(',').unwrap_or(start);
+        let end = start + "UNIQUE(collection_id, method, path)".len();
+        let rebuilt = format!("{}{}", &sql[..separator], &sql[end..]).replacen("CREATE TABLE routes", "CREATE TABLE routes_rebuilt", 1);
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&rebuilt).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO routes_rebuilt SELECT * FROM routes").execute(&mut *tx).await?;
+        sqlx::query("DROP TABLE routes").execute(&mut *tx).await?;
+        sqlx::query("ALTER TABLE routes_rebuilt RENAME TO routes").execute(&mut *tx).await?;
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -75,14 +172,31 @@ impl Database {
             description: req.description,
             port: req.port,
             base_path: req.base_path,
+            bandwidth_bytes_per_sec: req.bandwidth_bytes_per_sec,
+            asset_dir: req.asset_dir,
+            static_mounts: req.static_mounts,
+            kind: req.kind,
+            proto_files: req.proto_files,
+            openapi_spec: req.openapi_spec,
+            contract_check: req.contract_check,
+            admin_api: req.admin_api,
+            fallback: req.fallback,
+            path_normalization: req.path_normalization,
+            auth: req.auth,
+            oidc: req.oidc,
+            cors: req.cors,
+            rate_limit: req.rate_limit,
+            session: req.session,
+            compression: req.compression,
             created_at: now,
             updated_at: now,
         };
+        check_collection(&collection)?;
 
         sqlx::query(
             r#"
-            INSERT INTO collections (id, name, description, port, base_path, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO collections (id, name, description, port, base_path, bandwidth_bytes_per_sec, asset_dir, static_mounts, kind, proto_files, openapi_spec, contract_check, admin_api, fallback_config, path_normalization, auth_config, oidc_config, cors_config, rate_limit, session_config, compression_config, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)
             "#,
         )
         .bind(&collection.id)
@@ -90,6 +204,22 @@ impl Database {
         .bind(&collection.description)
         .bind(collection.port as i32)
         .bind(&collection.base_path)
+        .bind(collection.bandwidth_bytes_per_sec.map(|b| b as i64))
+        .bind(&collection.asset_dir)
+        .bind(serde_json::to_string(&collection.static_mounts)?)
+        .bind(collection.kind.as_str())
+        .bind(serde_json::to_string(&collection.proto_files)?)
+        .bind(&collection.openapi_spec)
+        .bind(collection.contract_check)
+        .bind(collection.admin_api)
+        .bind(collection.fallback.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.path_normalization)?)
+        .bind(collection.auth.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(collection.oidc.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.cors)?)
+        .bind(collection.rate_limit.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.session)?)
+        .bind(collection.compression.as_ref().map(serde_json::to_string).transpose()?)
         .bind(collection.created_at.to_rfc3339())
         .bind(collection.updated_at.to_rfc3339())
         .execute(&self.pool)
@@ -99,45 +229,20 @@ impl Database {
     }
 
     pub async fn get_collections(&self) -> Result<Vec<Collection>> {
-        let rows = sqlx::query_as::<_, (String, String, Option<String>, i32, Option<String>, String, String)>(
-            "SELECT id, name, description, port, base_path, created_at, updated_at FROM collections ORDER BY created_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = sqlx::query(&format!("SELECT {} FROM collections ORDER BY created_at DESC", COLLECTION_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
 
-        let collections = rows
-            .into_iter()
-            .map(|(id, name, description, port, base_path, created_at, updated_at)| Collection {
-                id,
-                name,
-                description,
-                port: port as u16,
-                base_path,
-                created_at: DateTime::parse_from_rfc3339(&created_at).unwrap().with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&updated_at).unwrap().with_timezone(&Utc),
-            })
-            .collect();
-
-        Ok(collections)
+        rows.iter().map(collection_from_row).collect()
     }
 
     pub async fn get_collection(&self, id: &str) -> Result<Option<Collection>> {
-        let row = sqlx::query_as::<_, (String, String, Option<String>, i32, Option<String>, String, String)>(
-            "SELECT id, name, description, port, base_path, created_at, updated_at FROM collections WHERE id = ?1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = sqlx::query(&format!("SELECT {} FROM collections WHERE id = ?1", COLLECTION_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        Ok(row.map(|(id, name, description, port, base_path, created_at, updated_at)| Collection {
-            id,
-            name,
-            description,
-            port: port as u16,
-            base_path,
-            created_at: DateTime::parse_from_rfc3339(&created_at).unwrap().with_timezone(&Utc),
-            updated_at: DateTime::parse_from_rfc3339(&updated_at).unwrap().with_timezone(&Utc),
-        }))
+        row.as_ref().map(collection_from_row).transpose()
     }
 
     pub async fn update_collection(&self, req: UpdateCollectionRequest) -> Result<Collection> {
@@ -156,13 +261,62 @@ impl Database {
         if let Some(base_path) = req.base_path {
             collection.base_path = Some(base_path);
         }
+        if req.bandwidth_bytes_per_sec.is_some() {
+            collection.bandwidth_bytes_per_sec = req.bandwidth_bytes_per_sec;
+        }
+        if let Some(asset_dir) = req.asset_dir {
+            collection.asset_dir = Some(asset_dir);
+        }
+        if let Some(static_mounts) = req.static_mounts {
+            collection.static_mounts = static_mounts;
+        }
+        if let Some(kind) = req.kind {
+            collection.kind = kind;
+        }
+        if let Some(proto_files) = req.proto_files {
+            collection.proto_files = proto_files;
+        }
+        if let Some(openapi_spec) = req.openapi_spec {
+            collection.openapi_spec = Some(openapi_spec);
+        }
+        if let Some(contract_check) = req.contract_check {
+            collection.contract_check = contract_check;
+        }
+        if let Some(admin_api) = req.admin_api {
+            collection.admin_api = admin_api;
+        }
+        if req.fallback.is_some() {
+            collection.fallback = req.fallback;
+        }
+        if let Some(path_normalization) = req.path_normalization {
+            collection.path_normalization = path_normalization;
+        }
+        if req.auth.is_some() {
+            collection.auth = req.auth;
+        }
+        if req.oidc.is_some() {
+            collection.oidc = req.oidc;
+        }
+        if let Some(cors) = req.cors {
+            collection.cors = cors;
+        }
+        if req.rate_limit.is_some() {
+            collection.rate_limit = req.rate_limit;
+        }
+        if let Some(session) = req.session {
+            collection.session = session;
+        }
+        if req.compression.is_some() {
+            collection.compression = req.compression;
+        }
 
+        check_collection(&collection)?;
         collection.updated_at = Utc::now();
 
         sqlx::query(
             r#"
             UPDATE collections 
-            SET name = ?2, description = ?3, port = ?4, base_path = ?5, updated_at = ?6
+            SET name = ?2, description = ?3, port = ?4, base_path = ?5, bandwidth_bytes_per_sec = ?6, asset_dir = ?7, static_mounts = ?8, kind = ?9, proto_files = ?10, openapi_spec = ?11, contract_check = ?12, admin_api = ?13, fallback_config = ?14, path_normalization = ?15, auth_config = ?16, oidc_config = ?17, cors_config = ?18, rate_limit = ?19, session_config = ?20, compression_config = ?21, updated_at = ?22
             WHERE id = ?1
             "#,
         )
@@ -171,6 +325,22 @@ impl Database {
         .bind(&collection.description)
         .bind(collection.port as i32)
         .bind(&collection.base_path)
+        .bind(collection.bandwidth_bytes_per_sec.map(|b| b as i64))
+        .bind(&collection.asset_dir)
+        .bind(serde_json::to_string(&collection.static_mounts)?)
+        .bind(collection.kind.as_str())
+        .bind(serde_json::to_string(&collection.proto_files)?)
+        .bind(&collection.openapi_spec)
+        .bind(collection.contract_check)
+        .bind(collection.admin_api)
+        .bind(collection.fallback.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.path_normalization)?)
+        .bind(collection.auth.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(collection.oidc.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.cors)?)
+        .bind(collection.rate_limit.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&collection.session)?)
+        .bind(collection.compression.as_ref().map(serde_json::to_string).transpose()?)
         .bind(collection.updated_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
@@ -195,31 +365,72 @@ impl Database {
             id: id.clone(),
             collection_id: req.collection_id,
             name: req.name,
+            kind: req.kind,
             method: req.method,
             path: req.path,
+            path_match: req.path_match,
             status_code: req.status_code,
             response_body: req.response_body,
+            body_source: req.body_source,
+            representations: req.representations,
+            sse: req.sse,
+            websocket: req.websocket,
+            graphql: req.graphql,
+            grpc: req.grpc,
+            validation: req.validation,
+            template: req.template,
+            fake_seed: req.fake_seed,
+            request_match: req.request_match,
+            priority: req.priority,
+            auth: req.auth,
+            rate_limit: req.rate_limit,
+            cookies: req.cookies,
+            session: req.session,
             response_headers: req.response_headers,
             delay_ms: req.delay_ms,
+            bandwidth_bytes_per_sec: req.bandwidth_bytes_per_sec,
             created_at: now,
             updated_at: now,
         };
+        let collection = self.get_collection(&route.collection_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Collection not found"))?;
+        check_route(&route, &collection)?;
 
         sqlx::query(
             r#"
-            INSERT INTO routes (id, collection_id, name, method, path, status_code, response_body, response_headers, delay_ms, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            INSERT INTO routes (id, collection_id, name, kind, method, path, path_match, status_code, response_body, body_source, body_file_path, response_blob, representations, sse_config, websocket_config, graphql_config, grpc_config, validation_config, template, fake_seed, request_match, priority, auth_config, rate_limit, cookies, session_update, response_headers, delay_ms, bandwidth_bytes_per_sec, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31)
             "#,
         )
         .bind(&route.id)
         .bind(&route.collection_id)
         .bind(&route.name)
+        .bind(route.kind.as_str())
         .bind(route.method.as_str())
         .bind(&route.path)
+        .bind(route.path_match.as_str())
         .bind(route.status_code as i32)
         .bind(&route.response_body)
-        .bind(route.response_headers.as_ref().map(|h| h.to_string()))
+        .bind(route.body_source.as_str())
+        .bind(body_file_path(&route.body_source))
+        .bind(body_blob(&route.body_source))
+        .bind(serde_json::to_string(&route.representations)?)
+        .bind(route.sse.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.websocket.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.graphql.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.grpc.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.validation.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.template)
+        .bind(route.fake_seed.map(|s| s as i64))
+        .bind(route.request_match.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.priority)
+        .bind(route.auth.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.rate_limit.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&route.cookies)?)
+        .bind(route.session.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&route.response_headers)?)
         .bind(route.delay_ms.map(|d| d as i32))
+        .bind(route.bandwidth_bytes_per_sec.map(|b| b as i64))
         .bind(route.created_at.to_rfc3339())
         .bind(route.updated_at.to_rfc3339())
         .execute(&self.pool)
@@ -229,54 +440,21 @@ impl Database {
     }
 
     pub async fn get_routes(&self, collection_id: &str) -> Result<Vec<Route>> {
-        let rows = sqlx::query_as::<_, (String, String, String, String, String, i32, Option<String>, Option<String>, Option<i32>, String, String)>(
-            "SELECT id, collection_id, name, method, path, status_code, response_body, response_headers, delay_ms, created_at, updated_at FROM routes WHERE collection_id = ?1 ORDER BY created_at DESC"
-        )
-        .bind(collection_id)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = sqlx::query(&format!("SELECT {} FROM routes WHERE collection_id = ?1 ORDER BY created_at DESC", ROUTE_COLUMNS))
+            .bind(collection_id)
+            .fetch_all(&self.pool)
+            .await?;
 
-        let routes = rows
-            .into_iter()
-            .map(|(id, collection_id, name, method, path, status_code, response_body, response_headers, delay_ms, created_at, updated_at)| Route {
-                id,
-                collection_id,
-                name,
-                method: serde_json::from_str(&format!("\"{}\"", method)).unwrap(),
-                path,
-                status_code: status_code as u16,
-                response_body,
-                response_headers: response_headers.and_then(|h| serde_json::from_str(&h).ok()),
-                delay_ms: delay_ms.map(|d| d as u32),
-                created_at: DateTime::parse_from_rfc3339(&created_at).unwrap().with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&updated_at).unwrap().with_timezone(&Utc),
-            })
-            .collect();
-
-        Ok(routes)
+        rows.iter().map(route_from_row).collect()
     }
 
     pub async fn get_route(&self, id: &str) -> Result<Option<Route>> {
-        let row = sqlx::query_as::<_, (String, String, String, String, String, i32, Option<String>, Option<String>, Option<i32>, String, String)>(
-            "SELECT id, collection_id, name, method, path, status_code, response_body, response_headers, delay_ms, created_at, updated_at FROM routes WHERE id = ?1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = sqlx::query(&format!("SELECT {} FROM routes WHERE id = ?1", ROUTE_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        Ok(row.map(|(id, collection_id, name, method, path, status_code, response_body, response_headers, delay_ms, created_at, updated_at)| Route {
-            id,
-            collection_id,
-            name,
-            method: serde_json::from_str(&format!("\"{}\"", method)).unwrap(),
-            path,
-            status_code: status_code as u16,
-            response_body,
-            response_headers: response_headers.and_then(|h| serde_json::from_str(&h).ok()),
-            delay_ms: delay_ms.map(|d| d as u32),
-            created_at: DateTime::parse_from_rfc3339(&created_at).unwrap().with_timezone(&Utc),
-            updated_at: DateTime::parse_from_rfc3339(&updated_at).unwrap().with_timezone(&Utc),
-        }))
+        row.as_ref().map(route_from_row).transpose()
     }
 
     pub async fn update_route(&self, req: UpdateRouteRequest) -> Result<Route> {
@@ -286,42 +464,119 @@ impl Database {
         if let Some(name) = req.name {
             route.name = name;
         }
+        if let Some(kind) = req.kind {
+            route.kind = kind;
+        }
         if let Some(method) = req.method {
             route.method = method;
         }
         if let Some(path) = req.path {
             route.path = path;
         }
+        if let Some(path_match) = req.path_match {
+            route.path_match = path_match;
+        }
         if let Some(status_code) = req.status_code {
             route.status_code = status_code;
         }
         if req.response_body.is_some() {
             route.response_body = req.response_body;
         }
-        if req.response_headers.is_some() {
-            route.response_headers = req.response_headers;
+        if let Some(body_source) = req.body_source {
+            route.body_source = body_source;
+        }
+        if let Some(representations) = req.representations {
+            route.representations = representations;
+        }
+        if req.sse.is_some() {
+            route.sse = req.sse;
+        }
+        if req.websocket.is_some() {
+            route.websocket = req.websocket;
+        }
+        if req.graphql.is_some() {
+            route.graphql = req.graphql;
+        }
+        if req.grpc.is_some() {
+            route.grpc = req.grpc;
+        }
+        if req.validation.is_some() {
+            route.validation = req.validation;
+        }
+        if let Some(template) = req.template {
+            route.template = template;
+        }
+        if req.fake_seed.is_some() {
+            route.fake_seed = req.fake_seed;
+        }
+        if req.request_match.is_some() {
+            route.request_match = req.request_match;
+        }
+        if let Some(priority) = req.priority {
+            route.priority = priority;
+        }
+        if req.auth.is_some() {
+            route.auth = req.auth;
+        }
+        if req.rate_limit.is_some() {
+            route.rate_limit = req.rate_limit;
+        }
+        if let Some(cookies) = req.cookies {
+            route.cookies = cookies;
+        }
+        if req.session.is_some() {
+            route.session = req.session;
+        }
+        if let Some(response_headers) = req.response_headers {
+            route.response_headers = response_headers;
         }
         if req.delay_ms.is_some() {
             route.delay_ms = req.delay_ms;
         }
+        if req.bandwidth_bytes_per_sec.is_some() {
+            route.bandwidth_bytes_per_sec = req.bandwidth_bytes_per_sec;
+        }
 
+        let collection = self.get_collection(&route.collection_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Collection not found"))?;
+        check_route(&route, &collection)?;
         route.updated_at = Utc::now();
 
         sqlx::query(
             r#"
             UPDATE routes 
-            SET name = ?2, method = ?3, path = ?4, status_code = ?5, response_body = ?6, response_headers = ?7, delay_ms = ?8, updated_at = ?9
+            SET name = ?2, kind = ?3, method = ?4, path = ?5, path_match = ?6, status_code = ?7, response_body = ?8, body_source = ?9, body_file_path = ?10, response_blob = ?11, representations = ?12, sse_config = ?13, websocket_config = ?14, graphql_config = ?15, grpc_config = ?16, validation_config = ?17, template = ?18, fake_seed = ?19, request_match = ?20, priority = ?21, auth_config = ?22, rate_limit = ?23, cookies = ?24, session_update = ?25, response_headers = ?26, delay_ms = ?27, bandwidth_bytes_per_sec = ?28, updated_at = ?29
             WHERE id = ?1
             "#,
         )
         .bind(&route.id)
         .bind(&route.name)
+        .bind(route.kind.as_str())
         .bind(route.method.as_str())
         .bind(&route.path)
+        .bind(route.path_match.as_str())
         .bind(route.status_code as i32)
         .bind(&route.response_body)
-        .bind(route.response_headers.as_ref().map(|h| h.to_string()))
+        .bind(route.body_source.as_str())
+        .bind(body_file_path(&route.body_source))
+        .bind(body_blob(&route.body_source))
+        .bind(serde_json::to_string(&route.representations)?)
+        .bind(route.sse.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.websocket.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.graphql.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.grpc.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.validation.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.template)
+        .bind(route.fake_seed.map(|s| s as i64))
+        .bind(route.request_match.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.priority)
+        .bind(route.auth.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(route.rate_limit.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&route.cookies)?)
+        .bind(route.session.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(serde_json::to_string(&route.response_headers)?)
         .bind(route.delay_ms.map(|d| d as i32))
+        .bind(route.bandwidth_bytes_per_sec.map(|b| b as i64))
         .bind(route.updated_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
@@ -336,4 +591,190 @@ impl Database {
             .await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+fn collection_from_row(row: &SqliteRow) -> Result<Collection> {
+    Ok(Collection {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        port: row.try_get::<i32, _>("port")? as u16,
+        base_path: row.try_get("base_path")?,
+        bandwidth_bytes_per_sec: row.try_get::<Option<i64>, _>("bandwidth_bytes_per_sec")?.map(|b| b as u32),
+        asset_dir: row.try_get("asset_dir")?,
+        static_mounts: row.try_get::<Option<String>, _>("static_mounts")?
+            .and_then(|m| serde_json::from_str(&m).ok())
+            .unwrap_or_default(),
+        kind: serde_json::from_str(&format!("\"{}\"", row.try_get::<String, _>("kind")?))?,
+        proto_files: row.try_get::<Option<String>, _>("proto_files")?
+            .and_then(|p| serde_json::from_str(&p).ok())
+            .unwrap_or_default(),
+        openapi_spec: row.try_get("openapi_spec")?,
+        contract_check: row.try_get("contract_check")?,
+        admin_api: row.try_get("admin_api")?,
+        fallback: row.try_get::<Option<String>, _>("fallback_config")?
+            .and_then(|f| serde_json::from_str(&f).ok()),
+        path_normalization: row.try_get::<Option<String>, _>("path_normalization")?
+            .and_then(|n| serde_json::from_str(&n).ok())
+            .unwrap_or_default(),
+        auth: row.try_get::<Option<String>, _>("auth_config")?
+            .and_then(|a| serde_json::from_str(&a).ok()),
+        oidc: row.try_get::<Option<String>, _>("oidc_config")?
+            .and_then(|o| serde_json::from_str(&o).ok()),
+        cors: row.try_get::<Option<String>, _>("cors_config")?
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default(),
+        rate_limit: row.try_get::<Option<String>, _>("rate_limit")?
+            .and_then(|r| serde_json::from_str(&r).ok()),
+        session: row.try_get::<Option<String>, _>("session_config")?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        compression: row.try_get::<Option<String>, _>("compression_config")?
+            .and_then(|c| serde_json::from_str(&c).ok()),
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+        updated_at: parse_timestamp(&row.try_get::<String, _>("updated_at")?)?,
+    })
+}
+
+/// Rejects route configuration that could never be served.
+fn check_route(route: &Route, collection: &Collection) -> Result<()> {
+    if let BodySource::File { ref path } = route.body_source {
+        mock_server::resolve_asset_path(collection, path).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(schema) = route.validation.as_ref().and_then(|v| v.body_schema.as_ref()) {
+        validation::compile_schema(schema).map_err(anyhow::Error::msg)?;
+    }
+    matching::check_path(route.path_match, &route.path).map_err(anyhow::Error::msg)?;
+    if let Some(ref config) = route.sse {
+        sse::check_config(config).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref config) = route.websocket {
+        websocket::check_config(config).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref config) = route.graphql {
+        graphql::check_config(config).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref request_match) = route.request_match {
+        matching::check_request_match(request_match).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref auth) = route.auth {
+        auth::check_config(auth).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref limit) = route.rate_limit {
+        rate_limit::check_config(limit).map_err(anyhow::Error::msg)?;
+    }
+    for cookie in &route.cookies {
+        cookies::check_cookie(cookie).map_err(anyhow::Error::msg)?;
+    }
+    mock_server::check_headers(&route.response_headers).map_err(anyhow::Error::msg)?;
+    content_type::check_representations(&route.representations).map_err(anyhow::Error::msg)?;
+    Ok(())
+}
+
+/// Rejects collection configuration that could never be served.
+fn check_collection(collection: &Collection) -> Result<()> {
+    if let Some(ref auth) = collection.auth {
+        auth::check_config(auth).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref config) = collection.oidc {
+        oidc::check_config(config).map_err(anyhow::Error::msg)?;
+    }
+    cors::layer(&collection.cors).map_err(anyhow::Error::msg)?;
+    if let Some(ref limit) = collection.rate_limit {
+        rate_limit::check_config(limit).map_err(anyhow::Error::msg)?;
+    }
+    cookies::check_name(&collection.session.cookie_name).map_err(anyhow::Error::msg)?;
+    if let Some(FallbackConfig::Response { ref headers, .. }) = collection.fallback {
+        mock_server::check_headers(headers).map_err(anyhow::Error::msg)?;
+    }
+    if let Some(ref config) = collection.compression {
+        compression::check_config(config).map_err(anyhow::Error::msg)?;
+    }
+    static_files::check_mounts(collection).map_err(anyhow::Error::msg)?;
+    Ok(())
+}
+
+fn route_from_row(row: &SqliteRow) -> Result<Route> {
+    let kind: String = row.try_get("kind")?;
+    let method: String = row.try_get("method")?;
+    let path_match: String = row.try_get("path_match")?;
+
+    Ok(Route {
+        id: row.try_get("id")?,
+        collection_id: row.try_get("collection_id")?,
+        name: row.try_get("name")?,
+        kind: serde_json::from_str(&format!("\"{}\"", kind))?,
+        method: serde_json::from_str(&format!("\"{}\"", method))?,
+        path: row.try_get("path")?,
+        path_match: serde_json::from_str(&format!("\"{}\"", path_match))?,
+        status_code: row.try_get::<i32, _>("status_code")? as u16,
+        response_body: row.try_get("response_body")?,
+        body_source: body_source_from_row(row)?,
+        representations: row.try_get::<Option<String>, _>("representations")?
+            .and_then(|r| serde_json::from_str(&r).ok())
+            .unwrap_or_default(),
+        sse: row.try_get::<Option<String>, _>("sse_config")?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        websocket: row.try_get::<Option<String>, _>("websocket_config")?
+            .and_then(|w| serde_json::from_str(&w).ok()),
+        graphql: row.try_get::<Option<String>, _>("graphql_config")?
+            .and_then(|g| serde_json::from_str(&g).ok()),
+        grpc: row.try_get::<Option<String>, _>("grpc_config")?
+            .and_then(|g| serde_json::from_str(&g).ok()),
+        validation: row.try_get::<Option<String>, _>("validation_config")?
+            .and_then(|v| serde_json::from_str(&v).ok()),
+        template: row.try_get("template")?,
+        fake_seed: row.try_get::<Option<i64>, _>("fake_seed")?.map(|s| s as u64),
+        request_match: row.try_get::<Option<String>, _>("request_match")?
+            .and_then(|m| serde_json::from_str(&m).ok()),
+        priority: row.try_get("priority")?,
+        auth: row.try_get::<Option<String>, _>("auth_config")?
+            .and_then(|a| serde_json::from_str(&a).ok()),
+        rate_limit: row.try_get::<Option<String>, _>("rate_limit")?
+            .and_then(|r| serde_json::from_str(&r).ok()),
+        cookies: row.try_get::<Option<String>, _>("cookies")?
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default(),
+        session: row.try_get::<Option<String>, _>("session_update")?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        response_headers: row.try_get::<Option<String>, _>("response_headers")?
+            .and_then(|h| serde_json::from_str(&h).ok())
+            .unwrap_or_default(),
+        delay_ms: row.try_get::<Option<i32>, _>("delay_ms")?.map(|d| d as u32),
+        bandwidth_bytes_per_sec: row.try_get::<Option<i64>, _>("bandwidth_bytes_per_sec")?.map(|b| b as u32),
+        created_at: parse_timestamp(&row.try_get::<String, _>("created_at")?)?,
+        updated_at: parse_timestamp(&row.try_get::<String, _>("updated_at")?)?,
+    })
+}
+
+fn body_source_from_row(row: &SqliteRow) -> Result<BodySource> {
+    let kind: String = row.try_get("body_source")?;
+
+    Ok(match kind.as_str() {
+        "file" => BodySource::File {
+            path: row.try_get::<Option<String>, _>("body_file_path")?.unwrap_or_default(),
+        },
+        "blob" => BodySource::Blob {
+            data: row.try_get::<Option<Vec<u8>>, _>("response_blob")?.unwrap_or_default(),
+        },
+        _ => BodySource::Inline,
+    })
+}
+
+fn body_file_path(source: &BodySource) -> Option<&str> {
+    match source {
+        BodySource::File { path } => Some(path),
+        _ => None,
+    }
+}
+
+fn body_blob(source: &BodySource) -> Option<&[u8]> {
+    match source {
+        BodySource::Blob { data } => Some(data),
+        _ => None,
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
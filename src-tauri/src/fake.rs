@@ -0,0 +1,550 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde_json::{Map, Number, Value};
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ben", "Carla", "Chen", "Diego", "Elena", "Farah", "Grace", "Hiro", "Ines",
+    "Jonas", "Kofi", "Lena", "Luis", "Maya", "Nikhil", "Olga", "Priya", "Quinn", "Rosa", "Sam", "Tariq",
+    "Uma", "Victor", "Wen", "Yara", "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adams", "Bauer", "Chen", "Diaz", "Evans", "Fischer", "Garcia", "Haddad", "Ito", "Jensen", "Kim",
+    "Lopez", "Martin", "Nakamura", "Okafor", "Patel", "Rossi", "Silva", "Tanaka", "Usman", "Varga",
+    "Walker", "Xu", "Young", "Zhang",
+];
+
+const STREETS: &[&str] = &[
+    "Maple", "Oak", "Cedar", "Elm", "Pine", "Willow", "Lake", "Hill", "Park", "River", "Sunset", "Mill",
+];
+
+const STREET_SUFFIXES: &[&str] = &["Street", "Avenue", "Road", "Lane", "Drive", "Way", "Boulevard"];
+
+const CITIES: &[&str] = &[
+    "Springfield", "Riverton", "Lakeside", "Fairview", "Greenville", "Madison", "Georgetown", "Ashford",
+    "Brookfield", "Clayton", "Milton", "Oakridge",
+];
+
+const COUNTRIES: &[&str] = &[
+    "Australia", "Brazil", "Canada", "Denmark", "France", "Germany", "India", "Japan", "Kenya", "Mexico",
+    "Netherlands", "Portugal", "Spain", "Sweden", "United Kingdom", "United States",
+];
+
+const COMPANY_WORDS: &[&str] = &[
+    "Acme", "Globex", "Initech", "Umbrella", "Stark", "Wayne", "Hooli", "Vandelay", "Soylent", "Tyrell",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "LLC", "Group", "Labs", "Systems", "Partners"];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net", "mail.test"];
+
+const LOREM: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do", "eiusmod",
+    "tempor", "incididunt", "ut", "labore", "et", "dolore", "magna", "aliqua", "enim", "ad", "minim",
+    "veniam", "quis", "nostrud", "exercitation", "ullamco", "laboris", "nisi", "aliquip", "ex", "ea",
+    "commodo", "consequat",
+];
+
+/// Deepest nesting `value_for_schema` follows, so recursive schemas still terminate.
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+/// The most items `value_for_schema` puts in one array.
+const MAX_ITEMS: u64 = 1000;
+
+/// The longest string `value_for_schema` generates.
+const MAX_STRING_LENGTH: u64 = 10_000;
+
+/// The most values `value_for_schema` generates in all, so nested arrays stay small.
+const MAX_VALUES: usize = 100_000;
+
+/// Generates realistic-looking fixture data. Two fakers built with the same seed
+/// produce the same sequence of values.
+pub struct Faker {
+    rng: ChaCha8Rng,
+    /// Dates are generated before this instant: now, or a fixed point when seeded so
+    /// that seeded output doesn't change from one day to the next.
+    anchor: DateTime<Utc>,
+}
+
+impl Faker {
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self {
+                rng: ChaCha8Rng::seed_from_u64(seed),
+                anchor: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            },
+            None => Self {
+                rng: ChaCha8Rng::from_entropy(),
+                anchor: Utc::now(),
+            },
+        }
+    }
+
+    fn pick(&mut self, items: &[&str]) -> String {
+        items.choose(&mut self.rng).copied().unwrap_or_default().to_string()
+    }
+
+    pub fn first_name(&mut self) -> String {
+        self.pick(FIRST_NAMES)
+    }
+
+    pub fn last_name(&mut self) -> String {
+        self.pick(LAST_NAMES)
+    }
+
+    pub fn name(&mut self) -> String {
+        format!("{} {}", self.first_name(), self.last_name())
+    }
+
+    pub fn username(&mut self) -> String {
+        format!("{}{}", self.first_name().to_lowercase(), self.int(1, 999))
+    }
+
+    pub fn email(&mut self) -> String {
+        let first = self.first_name().to_lowercase();
+        let last = self.last_name().to_lowercase();
+        format!("{}.{}@{}", first, last, self.pick(DOMAINS))
+    }
+
+    pub fn phone(&mut self) -> String {
+        format!("+1-{}-{}-{:04}", self.int(200, 999), self.int(200, 999), self.int(0, 9999))
+    }
+
+    pub fn street_address(&mut self) -> String {
+        format!("{} {} {}", self.int(1, 9999), self.pick(STREETS), self.pick(STREET_SUFFIXES))
+    }
+
+    pub fn city(&mut self) -> String {
+        self.pick(CITIES)
+    }
+
+    pub fn country(&mut self) -> String {
+        self.pick(COUNTRIES)
+    }
+
+    pub fn zip_code(&mut self) -> String {
+        format!("{:05}", self.int(501, 99950))
+    }
+
+    pub fn address(&mut self) -> String {
+        format!("{}, {} {}, {}", self.street_address(), self.city(), self.zip_code(), self.country())
+    }
+
+    pub fn company(&mut self) -> String {
+        format!("{} {}", self.pick(COMPANY_WORDS), self.pick(COMPANY_SUFFIXES))
+    }
+
+    pub fn url(&mut self) -> String {
+        format!("https://{}/{}", self.pick(DOMAINS), self.word())
+    }
+
+    pub fn ipv4(&mut self) -> String {
+        format!("{}.{}.{}.{}", self.int(1, 223), self.int(0, 255), self.int(0, 255), self.int(1, 254))
+    }
+
+    pub fn uuid(&mut self) -> String {
+        uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid().to_string()
+    }
+
+    /// An integer in `min..=max`; the bounds may come in either order.
+    pub fn int(&mut self, min: i64, max: i64) -> i64 {
+        self.rng.gen_range(min.min(max)..=min.max(max))
+    }
+
+    /// A number in `min..=max`, rounded to two decimal places. Bounds are clamped to a
+    /// quarter of `f64::MAX` either way, so the range's width stays finite even once
+    /// rand scales it, and NaN counts as 0.
+    pub fn float(&mut self, min: f64, max: f64) -> f64 {
+        let clamp = |bound: f64| if bound.is_nan() { 0.0 } else { bound.clamp(-f64::MAX / 4.0, f64::MAX / 4.0) };
+        let (min, max) = (clamp(min), clamp(max));
+        let value = self.rng.gen_range(min.min(max)..=min.max(max));
+        // Values too large to scale by 100 have no decimals worth rounding anyway
+        let rounded = (value * 100.0).round() / 100.0;
+        if rounded.is_finite() { rounded } else { value }
+    }
+
+    pub fn boolean(&mut self) -> bool {
+        self.rng.gen()
+    }
+
+    /// A date (`YYYY-MM-DD`) within the last five years.
+    pub fn date(&mut self) -> String {
+        self.past(5 * 365).date_naive().format("%Y-%m-%d").to_string()
+    }
+
+    /// An RFC 3339 timestamp within the last year.
+    pub fn datetime(&mut self) -> String {
+        self.past(365).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    }
+
+    fn past(&mut self, max_days: i64) -> DateTime<Utc> {
+        self.anchor - Duration::seconds(self.int(0, max_days * 86_400))
+    }
+
+    pub fn word(&mut self) -> String {
+        self.pick(LOREM)
+    }
+
+    pub fn words(&mut self, count: usize) -> String {
+        (0..count).map(|_| self.word()).collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn sentence(&mut self) -> String {
+        let count = self.int(6, 12) as usize;
+        let mut sentence = self.words(count);
+        if let Some(first) = sentence.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        sentence.push('.');
+        sentence
+    }
+
+    pub fn paragraph(&mut self) -> String {
+        let count = self.int(3, 6);
+        (0..count).map(|_| self.sentence()).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Evaluates a `fake.*` template helper, given the part after `fake.` and its
+    /// space-separated arguments: `name`, `int 1 10`, `enum red green blue`, `words 5`.
+    pub fn helper(&mut self, name: &str, args: &[&str]) -> Option<String> {
+        let number = |index: usize, default: f64| args.get(index).and_then(|a| a.parse::<f64>().ok()).unwrap_or(default);
+
+        let value = match name {
+            "first_name" => self.first_name(),
+            "last_name" => self.last_name(),
+            "name" => self.name(),
+            "username" => self.username(),
+            "email" => self.email(),
+            "phone" => self.phone(),
+            "street_address" => self.street_address(),
+            "city" => self.city(),
+            "country" => self.country(),
+            "zip_code" => self.zip_code(),
+            "address" => self.address(),
+            "company" => self.company(),
+            "url" => self.url(),
+            "ipv4" => self.ipv4(),
+            "uuid" => self.uuid(),
+            "int" => self.int(number(0, 0.0) as i64, number(1, 1000.0) as i64).to_string(),
+            "float" => self.float(number(0, 0.0), number(1, 1000.0)).to_string(),
+            "bool" => self.boolean().to_string(),
+            "date" => self.date(),
+            "datetime" => self.datetime(),
+            "word" => self.word(),
+            "words" => self.words(number(0, 3.0) as usize),
+            "sentence" => self.sentence(),
+            "paragraph" => self.paragraph(),
+            "enum" => self.pick(args),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Builds a value that satisfies a JSON Schema, using `const`, `enum` and `example`
+    /// when given and otherwise generating data from the type, `format` and property name.
+    /// Local `$ref`s (`#/definitions/...`, `#/$defs/...`) are followed. Fails when the
+    /// schema asks for more than `MAX_ITEMS` items in an array, strings longer than
+    /// `MAX_STRING_LENGTH`, or more than `MAX_VALUES` values in all.
+    pub fn value_for_schema(&mut self, schema: &Value) -> Result<Value, String> {
+        let mut remaining = MAX_VALUES;
+        self.schema_value(schema, schema, None, 0, &mut remaining)
+    }
+
+    fn schema_value(
+        &mut self,
+        root: &Value,
+        schema: &Value,
+        property: Option<&str>,
+        depth: usize,
+        remaining: &mut usize,
+    ) -> Result<Value, String> {
+        if depth > MAX_SCHEMA_DEPTH {
+            return Ok(Value::Null);
+        }
+        *remaining = remaining
+            .checked_sub(1)
+            .ok_or_else(|| format!("Schema asks for more than {} values", MAX_VALUES))?;
+
+        let schema = match schema.get("$ref").and_then(Value::as_str) {
+            Some(reference) => match reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer)) {
+                Some(target) => return self.schema_value(root, target, property, depth + 1, remaining),
+                None => return Ok(Value::Null),
+            },
+            None => schema,
+        };
+
+        if let Some(value) = schema.get("const") {
+            return Ok(value.clone());
+        }
+        if let Some(choices) = schema.get("enum").and_then(Value::as_array) {
+            return Ok(choices.choose(&mut self.rng).cloned().unwrap_or(Value::Null));
+        }
+        if let Some(example) = schema.get("example") {
+            return Ok(example.clone());
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(branches) = schema.get(key).and_then(Value::as_array) {
+                if let Some(branch) = branches.choose(&mut self.rng) {
+                    return self.schema_value(root, branch, property, depth + 1, remaining);
+                }
+            }
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                if let Value::Object(object) = self.schema_value(root, part, property, depth + 1, remaining)? {
+                    merged.extend(object);
+                }
+            }
+            return Ok(Value::Object(merged));
+        }
+
+        let declared = match schema.get("type") {
+            Some(Value::String(kind)) => Some(kind.as_str()),
+            Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).find(|kind| *kind != "null"),
+            _ => None,
+        };
+        let kind = declared.unwrap_or(if schema.get("properties").is_some() {
+            "object"
+        } else if schema.get("items").is_some() {
+            "array"
+        } else {
+            "string"
+        });
+
+        let value = match kind {
+            "object" => {
+                let mut object = Map::new();
+                for (name, property_schema) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+                    let value = self.schema_value(root, property_schema, Some(name), depth + 1, remaining)?;
+                    object.insert(name.clone(), value);
+                }
+                Value::Object(object)
+            }
+            "array" => {
+                let min = count_bound(schema, "minItems").unwrap_or(1);
+                if min > MAX_ITEMS {
+                    return Err(format!("minItems {} is more than the {} items an array can have", min, MAX_ITEMS));
+                }
+                let max = count_bound(schema, "maxItems").unwrap_or(min.max(3)).clamp(min, MAX_ITEMS);
+                let count = self.int(min as i64, max as i64);
+                let items = schema.get("items").cloned().unwrap_or(Value::Bool(true));
+                let items = (0..count)
+                    .map(|_| self.schema_value(root, &items, property, depth + 1, remaining))
+                    .collect::<Result<_, _>>()?;
+                Value::Array(items)
+            }
+            "integer" => {
+                let (min, max) = range(schema, 1.0, (1.0, 1000.0));
+                Value::from(self.int(min.ceil() as i64, max.floor() as i64))
+            }
+            "number" => {
+                let (min, max) = range(schema, 0.01, (0.0, 1000.0));
+                Number::from_f64(self.float(min, max)).map(Value::Number).unwrap_or(Value::Null)
+            }
+            "boolean" => Value::Bool(self.boolean()),
+            "null" => Value::Null,
+            _ => Value::String(self.schema_string(schema, property)?),
+        };
+        Ok(value)
+    }
+
+    fn schema_string(&mut self, schema: &Value, property: Option<&str>) -> Result<String, String> {
+        let generated = match schema.get("format").and_then(Value::as_str) {
+            Some("email") => self.email(),
+            Some("uuid") => self.uuid(),
+            Some("date") => self.date(),
+            Some("date-time") => self.datetime(),
+            Some("time") => self.datetime()[11..19].to_string(),
+            Some("uri" | "url") => self.url(),
+            Some("hostname") => self.pick(DOMAINS),
+            Some("ipv4") => self.ipv4(),
+            _ => self.string_for_property(property.unwrap_or_default()),
+        };
+
+        let min = count_bound(schema, "minLength").unwrap_or(0);
+        if min > MAX_STRING_LENGTH {
+            return Err(format!("minLength {} is more than the {} characters a string can have", min, MAX_STRING_LENGTH));
+        }
+        let max = count_bound(schema, "maxLength").map(|max| max as usize);
+        Ok(fit_length(generated, min as usize, max, || self.word()))
+    }
+
+    /// Guesses a fitting generator from a property name such as `email` or `billing_city`.
+    fn string_for_property(&mut self, property: &str) -> String {
+        let name = property.to_ascii_lowercase().replace(['_', '-'], "");
+        let has = |needle: &str| name.contains(needle);
+
+        if has("email") {
+            self.email()
+        } else if has("firstname") || name == "given" {
+            self.first_name()
+        } else if has("lastname") || has("surname") {
+            self.last_name()
+        } else if has("username") || has("login") {
+            self.username()
+        } else if has("company") || has("organization") {
+            self.company()
+        } else if has("name") {
+            self.name()
+        } else if has("phone") {
+            self.phone()
+        } else if has("city") {
+            self.city()
+        } else if has("country") {
+            self.country()
+        } else if has("zip") || has("postal") {
+            self.zip_code()
+        } else if has("street") || has("address") {
+            self.street_address()
+        } else if has("url") || has("website") || has("link") {
+            self.url()
+        } else if name == "id" || name.ends_with("id") {
+            self.uuid()
+        } else if has("date") || name.ends_with("at") {
+            self.datetime()
+        } else if has("description") || has("summary") || has("bio") {
+            self.sentence()
+        } else {
+            self.words(2)
+        }
+    }
+}
+
+/// The inclusive range a numeric schema allows. Exclusive bounds are pulled in by
+/// `step`, and a missing bound is placed so the range keeps the default's width.
+fn range(schema: &Value, step: f64, (default_min, default_max): (f64, f64)) -> (f64, f64) {
+    let bound = |inclusive: &str, exclusive: &str, step: f64| {
+        schema
+            .get(inclusive)
+            .and_then(Value::as_f64)
+            .or_else(|| schema.get(exclusive).and_then(Value::as_f64).map(|value| value + step))
+    };
+    let span = default_max - default_min;
+
+    match (bound("minimum", "exclusiveMinimum", step), bound("maximum", "exclusiveMaximum", -step)) {
+        (Some(min), Some(max)) => (min, max),
+        (Some(min), None) => (min, min + span),
+        (None, Some(max)) => (max - span, max),
+        (None, None) => (default_min, default_max),
+    }
+}
+
+/// A count such as `minItems`, which may be written as `5` or `5.0`.
+fn count_bound(schema: &Value, key: &str) -> Option<u64> {
+    let value = schema.get(key)?;
+    value.as_u64().or_else(|| value.as_f64().filter(|count| *count >= 0.0).map(|count| count as u64))
+}
+
+fn fit_length(mut value: String, min: usize, max: Option<usize>, mut filler: impl FnMut() -> String) -> String {
+    while value.chars().count() < min {
+        value.push(' ');
+        value.push_str(&filler());
+    }
+    if let Some(max) = max {
+        value = value.chars().take(max).collect();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::template;
+
+    fn user_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "string", "format": "uuid" },
+                "email": { "type": "string" },
+                "age": { "type": "integer", "minimum": 18, "maximum": 99 },
+                "score": { "type": "number" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "joined": { "type": "string", "format": "date-time" }
+            }
+        })
+    }
+
+    #[test]
+    fn same_seed_gives_same_schema_values() {
+        let first = Faker::new(Some(42)).value_for_schema(&user_schema()).unwrap();
+        let second = Faker::new(Some(42)).value_for_schema(&user_schema()).unwrap();
+        assert_eq!(first, second);
+
+        let other = Faker::new(Some(43)).value_for_schema(&user_schema()).unwrap();
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn same_seed_gives_same_template_helpers() {
+        let text = "{{ fake.name }} {{ fake.int 1 100 }} {{ fake.float 0 1 }} {{ fake.uuid }} {{ fake.datetime }} \
+                    {{#repeat 3}}{{ fake.email }}{{/repeat}}";
+        let render = |seed| template::render_with(text, &json!({}), &mut Faker::new(Some(seed)));
+
+        assert_eq!(render(7), render(7));
+        assert_ne!(render(7), render(8));
+    }
+
+    #[test]
+    fn numbers_stay_within_bounds() {
+        let integer = json!({ "type": "integer", "minimum": 5, "maximum": 10 });
+        let number = json!({ "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 });
+
+        for seed in 0..200 {
+            let mut faker = Faker::new(Some(seed));
+            let value = faker.value_for_schema(&integer).unwrap().as_i64().unwrap();
+            assert!((5..=10).contains(&value), "{} out of range", value);
+
+            let value = faker.value_for_schema(&number).unwrap().as_f64().unwrap();
+            assert!(value > 0.0 && value < 1.0, "{} out of range", value);
+        }
+    }
+
+    #[test]
+    fn enum_values_are_chosen_from_the_list() {
+        let schema = json!({ "type": "string", "enum": ["red", "green", "blue"] });
+
+        for seed in 0..50 {
+            let value = Faker::new(Some(seed)).value_for_schema(&schema).unwrap();
+            assert!(["red", "green", "blue"].contains(&value.as_str().unwrap()), "unexpected {}", value);
+        }
+    }
+
+    #[test]
+    fn strings_follow_their_format() {
+        let generate = |format: &str| {
+            let schema = json!({ "type": "string", "format": format });
+            Faker::new(Some(1)).value_for_schema(&schema).unwrap().as_str().unwrap().to_string()
+        };
+
+        assert!(uuid::Uuid::parse_str(&generate("uuid")).is_ok());
+        assert!(chrono::NaiveDate::parse_from_str(&generate("date"), "%Y-%m-%d").is_ok());
+        assert!(DateTime::parse_from_rfc3339(&generate("date-time")).is_ok());
+        assert!(generate("ipv4").parse::<std::net::Ipv4Addr>().is_ok());
+        assert!(generate("email").contains('@'));
+        assert!(generate("uri").starts_with("https://"));
+    }
+
+    #[test]
+    fn oversized_schemas_are_rejected() {
+        let mut faker = Faker::new(Some(1));
+
+        assert!(faker.value_for_schema(&json!({ "type": "array", "minItems": 1_000_000_000 })).is_err());
+        assert!(faker.value_for_schema(&json!({ "type": "string", "minLength": 1e9 })).is_err());
+
+        let nested = json!({
+            "type": "array", "minItems": 1000,
+            "items": { "type": "array", "minItems": 1000, "items": { "type": "integer" } }
+        });
+        assert!(faker.value_for_schema(&nested).is_err());
+
+        let capped = faker.value_for_schema(&json!({ "type": "array", "maxItems": 1_000_000, "minItems": 2000 }));
+        assert!(capped.is_err());
+        let capped = faker.value_for_schema(&json!({ "type": "array", "minItems": 990, "maxItems": 1_000_000 })).unwrap();
+        assert!(capped.as_array().unwrap().len() <= MAX_ITEMS as usize);
+    }
+}
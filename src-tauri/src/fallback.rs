@@ -0,0 +1,125 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::matching::{self, RoutePatterns};
+use crate::mock_server;
+use crate::models::{Collection, FallbackConfig, PathNormalization, Route};
+use crate::route_cache::RouteCache;
+
+/// A route the request came close to matching, and why it didn't.
+#[derive(Serialize)]
+struct NearMiss<'a> {
+    route_id: &'a str,
+    route_name: &'a str,
+    method: &'a str,
+    path: &'a str,
+    reason: String,
+}
+
+/// Answers a request that neither a route nor a static mount matched, as the
+/// collection's fallback config says. `patterns` holds the routes' compiled regexes.
+pub fn respond(
+    collection: &Collection,
+    routes: &[Route],
+    patterns: &RouteCache<RoutePatterns>,
+    method: &Method,
+    uri: &Uri,
+    path: &str,
+) -> Response {
+    match collection.fallback.as_ref() {
+        None => (StatusCode::NOT_FOUND, "Route not found").into_response(),
+        Some(FallbackConfig::Response { status_code, body, headers }) => {
+            let body = Bytes::from(body.clone().unwrap_or_default());
+            let headers = mock_server::build_headers(headers, &body, None);
+
+            let mut response = Response::new(Body::from(body));
+            *response.status_mut() = StatusCode::from_u16(*status_code).unwrap_or(StatusCode::NOT_FOUND);
+            *response.headers_mut() = headers;
+            response
+        }
+        Some(FallbackConfig::ClosestMatch) => {
+            let near_misses: Vec<NearMiss> = routes
+                .iter()
+                .filter_map(|route| {
+                    let patterns = patterns.get(route, || RoutePatterns::compile(route)).ok()?;
+                    Some(NearMiss {
+                        route_id: &route.id,
+                        route_name: &route.name,
+                        method: route.method.as_str(),
+                        path: &route.path,
+                        reason: near_miss(&collection.path_normalization, route, &patterns, method, path)?,
+                    })
+                })
+                .collect();
+
+            let body = json!({
+                "error": "Route not found",
+                "method": method.as_str(),
+                "path": path,
+                "near_misses": near_misses,
+            });
+            (StatusCode::NOT_FOUND, Json(body)).into_response()
+        }
+        Some(FallbackConfig::Redirect { location, status_code, append_path }) => {
+            let location = if *append_path {
+                let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+                format!("{}{}", location.trim_end_matches('/'), path_and_query)
+            } else {
+                location.clone()
+            };
+            let status = StatusCode::from_u16(*status_code)
+                .ok()
+                .filter(StatusCode::is_redirection)
+                .unwrap_or(StatusCode::TEMPORARY_REDIRECT);
+
+            match HeaderValue::from_str(&location) {
+                Ok(location) => (status, [(header::LOCATION, location)]).into_response(),
+                Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Invalid fallback redirect location").into_response(),
+            }
+        }
+    }
+}
+
+/// Explains how the request missed `route`, or returns `None` when it wasn't close.
+fn near_miss(
+    options: &PathNormalization,
+    route: &Route,
+    patterns: &RoutePatterns,
+    method: &Method,
+    path: &str,
+) -> Option<String> {
+    let method_matches = mock_server::method_matches(method, &route.method);
+    let path_matches = |options: &PathNormalization, path: &str| {
+        matching::match_path(options, route, patterns, path).is_some()
+    };
+
+    if path_matches(options, path) {
+        // A route matching both method and path can only have missed on `request_match`
+        return Some(if method_matches {
+            "The request doesn't meet the route's match conditions".to_string()
+        } else {
+            format!("The route expects {}, not {}", route.method.as_str(), method)
+        });
+    }
+    if !method_matches {
+        return None;
+    }
+
+    let toggled = match path.strip_suffix('/') {
+        Some(trimmed) if !trimmed.is_empty() => trimmed.to_string(),
+        Some(_) => return None,
+        None => format!("{}/", path),
+    };
+    if path_matches(options, &toggled) {
+        return Some(format!("The paths differ by a trailing slash; try {}", toggled));
+    }
+
+    let ignoring_case = PathNormalization { case_insensitive: true, ..options.clone() };
+    path_matches(&ignoring_case, path).then(|| "The paths differ only in letter case".to_string())
+}
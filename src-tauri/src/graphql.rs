@@ -0,0 +1,748 @@
+use graphql_parser::query::{
+    self as q, Definition, OperationDefinition, Selection, SelectionSet, TypeCondition,
+};
+use graphql_parser::schema::{self as s, TypeDefinition};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::matching::json_contains;
+use crate::models::{GraphQlConfig, GraphQlOperation};
+
+const BUILTIN_SCALARS: [&str; 5] = ["Int", "Float", "String", "Boolean", "ID"];
+
+/// Items generated for list fields that have no configured data.
+const FAKE_LIST_LENGTH: usize = 2;
+
+/// The standard GraphQL-over-HTTP request body.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQlRequest {
+    pub query: String,
+    pub operation_name: Option<String>,
+    #[serde(default)]
+    pub variables: Option<Value>,
+}
+
+/// Rejects a schema that doesn't parse or has no query type.
+pub fn check_config(config: &GraphQlConfig) -> Result<(), String> {
+    Schema::parse(&config.schema).map(|_| ())
+}
+
+/// Executes a GraphQL request against the route's schema.
+///
+/// The configured operation whose name and variables match supplies the result data;
+/// every selected field it doesn't cover is filled with fake data of the right type.
+/// `__schema`, `__type` and `__typename` are answered from the schema itself.
+pub fn execute(schema: &Schema, config: &GraphQlConfig, request: &GraphQlRequest) -> Value {
+    let query_document = match graphql_parser::parse_query::<&str>(&request.query) {
+        Ok(document) => document,
+        Err(e) => return error_response(format!("Syntax error: {}", e)),
+    };
+
+    let operation = match select_operation(&query_document, request.operation_name.as_deref()) {
+        Ok(operation) => operation,
+        Err(e) => return error_response(e),
+    };
+
+    let (operation_name, root_type, selection_set) = match operation {
+        OperationDefinition::SelectionSet(set) => (None, Some(schema.query_type.clone()), set),
+        OperationDefinition::Query(query) => (query.name, Some(schema.query_type.clone()), &query.selection_set),
+        OperationDefinition::Mutation(mutation) => (mutation.name, schema.mutation_type.clone(), &mutation.selection_set),
+        OperationDefinition::Subscription(subscription) => {
+            (subscription.name, schema.subscription_type.clone(), &subscription.selection_set)
+        }
+    };
+    let Some(root_type) = root_type else {
+        return error_response("Schema does not support this operation type".to_string());
+    };
+
+    let variables = request.variables.clone().unwrap_or(Value::Null);
+    let configured = find_operation(&config.operations, request.operation_name.as_deref().or(operation_name), &variables);
+
+    if let Some(operation) = configured {
+        if operation.data.is_none() {
+            if let Some(ref errors) = operation.errors {
+                return json!({ "data": null, "errors": errors });
+            }
+        }
+    }
+
+    let fragments = query_document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) => Some((fragment.name, fragment)),
+            _ => None,
+        })
+        .collect();
+
+    let mut executor = Executor {
+        schema,
+        fragments,
+        variables: &variables,
+        errors: Vec::new(),
+        counter: 0,
+    };
+
+    let source = configured.and_then(|operation| operation.data.as_ref());
+    let data = executor.resolve_object(&root_type, selection_set, source, true);
+
+    let mut response = Map::new();
+    response.insert("data".to_string(), Value::Object(data));
+
+    let mut errors: Vec<Value> = executor.errors.into_iter().map(|message| json!({ "message": message })).collect();
+    if let Some(Value::Array(configured_errors)) = configured.and_then(|operation| operation.errors.clone()) {
+        errors.extend(configured_errors);
+    }
+    if !errors.is_empty() {
+        response.insert("errors".to_string(), Value::Array(errors));
+    }
+
+    Value::Object(response)
+}
+
+pub fn error_response(message: String) -> Value {
+    json!({ "data": null, "errors": [{ "message": message }] })
+}
+
+fn select_operation<'q, 'a>(
+    document: &'q q::Document<'a, &'a str>,
+    operation_name: Option<&str>,
+) -> Result<&'q OperationDefinition<'a, &'a str>, String> {
+    let operations: Vec<_> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            _ => None,
+        })
+        .collect();
+
+    match operation_name {
+        Some(name) => operations
+            .into_iter()
+            .find(|operation| operation_name_of(operation) == Some(name))
+            .ok_or_else(|| format!("Unknown operation named \"{}\"", name)),
+        None if operations.len() == 1 => Ok(operations[0]),
+        None if operations.is_empty() => Err("No operation found in query".to_string()),
+        None => Err("Must provide operation name if query contains multiple operations".to_string()),
+    }
+}
+
+fn operation_name_of<'a>(operation: &OperationDefinition<'a, &'a str>) -> Option<&'a str> {
+    match operation {
+        OperationDefinition::SelectionSet(_) => None,
+        OperationDefinition::Query(query) => query.name,
+        OperationDefinition::Mutation(mutation) => mutation.name,
+        OperationDefinition::Subscription(subscription) => subscription.name,
+    }
+}
+
+/// The first configured operation with a matching name whose variables are a subset of
+/// the request's. Entries without a name match any operation.
+fn find_operation<'c>(
+    operations: &'c [GraphQlOperation],
+    operation_name: Option<&str>,
+    variables: &Value,
+) -> Option<&'c GraphQlOperation> {
+    operations.iter().find(|operation| {
+        let name_matches = match operation.operation_name {
+            Some(ref name) => operation_name == Some(name.as_str()),
+            None => true,
+        };
+        let variables_match = match operation.variables {
+            Some(ref expected) => json_contains(variables, expected),
+            None => true,
+        };
+        name_matches && variables_match
+    })
+}
+
+/// A route's parsed SDL, with its types looked up by name and its introspection result
+/// built up front.
+pub struct Schema {
+    types: HashMap<String, TypeDefinition<'static, String>>,
+    directives: Vec<s::DirectiveDefinition<'static, String>>,
+    query_type: String,
+    mutation_type: Option<String>,
+    subscription_type: Option<String>,
+    introspection: Value,
+}
+
+impl Schema {
+    /// Parses SDL, failing on syntax errors or when the query type isn't defined.
+    pub fn parse(sdl: &str) -> Result<Self, String> {
+        let document = graphql_parser::parse_schema::<String>(sdl)
+            .map_err(|e| format!("Invalid GraphQL schema: {}", e))?
+            .into_static();
+
+        let mut types = HashMap::new();
+        let mut directives = Vec::new();
+        let mut roots = (None, None, None);
+
+        for definition in document.definitions {
+            match definition {
+                s::Definition::TypeDefinition(definition) => {
+                    types.insert(type_definition_name(&definition).to_string(), definition);
+                }
+                s::Definition::SchemaDefinition(schema) => {
+                    roots = (schema.query, schema.mutation, schema.subscription);
+                }
+                s::Definition::DirectiveDefinition(directive) => directives.push(directive),
+                s::Definition::TypeExtension(_) => {}
+            }
+        }
+
+        let root = |explicit: Option<String>, default: &str| {
+            explicit.or_else(|| types.contains_key(default).then(|| default.to_string()))
+        };
+
+        let mut schema = Self {
+            query_type: root(roots.0, "Query").unwrap_or_else(|| "Query".to_string()),
+            mutation_type: root(roots.1, "Mutation"),
+            subscription_type: root(roots.2, "Subscription"),
+            types,
+            directives,
+            introspection: Value::Null,
+        };
+        if !schema.types.contains_key(&schema.query_type) {
+            return Err(format!("GraphQL schema has no {} type", schema.query_type));
+        }
+        schema.introspection = introspect_schema(&schema);
+        Ok(schema)
+    }
+
+    fn fields(&self, type_name: &str) -> Option<&[s::Field<'static, String>]> {
+        match self.types.get(type_name)? {
+            TypeDefinition::Object(object) => Some(&object.fields),
+            TypeDefinition::Interface(interface) => Some(&interface.fields),
+            _ => None,
+        }
+    }
+
+    /// The introspection `kind` of a named type; unknown names are treated as scalars.
+    fn kind_of(&self, type_name: &str) -> &'static str {
+        match self.types.get(type_name) {
+            Some(TypeDefinition::Object(_)) => "OBJECT",
+            Some(TypeDefinition::Interface(_)) => "INTERFACE",
+            Some(TypeDefinition::Union(_)) => "UNION",
+            Some(TypeDefinition::Enum(_)) => "ENUM",
+            Some(TypeDefinition::InputObject(_)) => "INPUT_OBJECT",
+            Some(TypeDefinition::Scalar(_)) | None => "SCALAR",
+        }
+    }
+
+    /// Whether an object of `type_name` satisfies a fragment's type condition.
+    fn applies(&self, type_name: &str, condition: &str) -> bool {
+        if type_name == condition {
+            return true;
+        }
+        match self.types.get(condition) {
+            Some(TypeDefinition::Union(union)) => union.types.iter().any(|member| member == type_name),
+            Some(TypeDefinition::Interface(_)) => self.implementations(condition).contains(&type_name),
+            _ => false,
+        }
+    }
+
+    fn implementations(&self, interface: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .types
+            .values()
+            .filter_map(|definition| match definition {
+                TypeDefinition::Object(object) if object.implements_interfaces.iter().any(|i| i == interface) => {
+                    Some(object.name.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// The concrete object types an abstract type may resolve to.
+    fn possible_types(&self, type_name: &str) -> Vec<&str> {
+        match self.types.get(type_name) {
+            Some(TypeDefinition::Union(union)) => union.types.iter().map(String::as_str).collect(),
+            Some(TypeDefinition::Interface(_)) => self.implementations(type_name),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn type_definition_name<'d>(definition: &'d TypeDefinition<'static, String>) -> &'d str {
+    match definition {
+        TypeDefinition::Scalar(scalar) => &scalar.name,
+        TypeDefinition::Object(object) => &object.name,
+        TypeDefinition::Interface(interface) => &interface.name,
+        TypeDefinition::Union(union) => &union.name,
+        TypeDefinition::Enum(enumeration) => &enumeration.name,
+        TypeDefinition::InputObject(input) => &input.name,
+    }
+}
+
+struct Executor<'e, 'q> {
+    schema: &'e Schema,
+    fragments: HashMap<&'q str, &'q q::FragmentDefinition<'q, &'q str>>,
+    variables: &'e Value,
+    errors: Vec<String>,
+    /// Makes generated values vary from field to field while staying deterministic.
+    counter: u64,
+}
+
+impl<'e, 'q> Executor<'e, 'q> {
+    fn resolve_object(
+        &mut self,
+        type_name: &str,
+        selection_set: &'q SelectionSet<'q, &'q str>,
+        source: Option<&Value>,
+        is_root: bool,
+    ) -> Map<String, Value> {
+        let mut result = Map::new();
+
+        for field in self.collect_fields(type_name, selection_set) {
+            let key = field.alias.unwrap_or(field.name).to_string();
+
+            let value = match field.name {
+                "__typename" => Value::String(type_name.to_string()),
+                "__schema" if is_root => resolve_json(&self.schema.introspection, &field.selection_set, &self.fragments),
+                "__type" if is_root => {
+                    let name = self.argument(field, "name").and_then(|v| v.as_str().map(str::to_string));
+                    match name.and_then(|name| self.schema.types.get(name.as_str()).map(|_| name)) {
+                        Some(name) => resolve_json(&introspect_type_named(self.schema, &name), &field.selection_set, &self.fragments),
+                        None => Value::Null,
+                    }
+                }
+                name => {
+                    let definition = self
+                        .schema
+                        .fields(type_name)
+                        .and_then(|fields| fields.iter().find(|f| f.name == name));
+                    match definition {
+                        Some(definition) => {
+                            let field_source = source.and_then(|s| s.get(name));
+                            self.resolve_value(&definition.field_type, &field.selection_set, field_source, name)
+                        }
+                        None => {
+                            self.errors.push(format!("Cannot query field \"{}\" on type \"{}\".", name, type_name));
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            result.insert(key, value);
+        }
+
+        result
+    }
+
+    fn resolve_value(
+        &mut self,
+        field_type: &s::Type<'static, String>,
+        selection_set: &'q SelectionSet<'q, &'q str>,
+        source: Option<&Value>,
+        field_name: &str,
+    ) -> Value {
+        if matches!(source, Some(Value::Null)) {
+            return Value::Null;
+        }
+
+        match field_type {
+            s::Type::NonNullType(inner) => self.resolve_value(inner, selection_set, source, field_name),
+            s::Type::ListType(inner) => match source {
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .map(|item| self.resolve_value(inner, selection_set, Some(item), field_name))
+                    .collect(),
+                _ => (0..FAKE_LIST_LENGTH)
+                    .map(|_| self.resolve_value(inner, selection_set, None, field_name))
+                    .collect(),
+            },
+            s::Type::NamedType(name) => match self.schema.types.get(name.as_str()) {
+                Some(TypeDefinition::Object(_)) => {
+                    Value::Object(self.resolve_object(name, selection_set, source, false))
+                }
+                Some(TypeDefinition::Interface(_)) | Some(TypeDefinition::Union(_)) => {
+                    let concrete = source
+                        .and_then(|s| s.get("__typename"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .or_else(|| self.schema.possible_types(name).first().map(|t| t.to_string()));
+                    match concrete {
+                        Some(concrete) => Value::Object(self.resolve_object(&concrete, selection_set, source, false)),
+                        None => Value::Null,
+                    }
+                }
+                Some(TypeDefinition::Enum(enumeration)) => match source {
+                    Some(value) => value.clone(),
+                    None => enumeration
+                        .values
+                        .get(self.next() as usize % enumeration.values.len().max(1))
+                        .map(|value| Value::String(value.name.to_string()))
+                        .unwrap_or(Value::Null),
+                },
+                _ => match source {
+                    Some(value) => value.clone(),
+                    None => self.fake_scalar(name, field_name),
+                },
+            },
+        }
+    }
+
+    /// Flattens fragments into the list of fields that apply to `type_name`, honoring
+    /// `@skip` and `@include`.
+    fn collect_fields(
+        &self,
+        type_name: &str,
+        selection_set: &'q SelectionSet<'q, &'q str>,
+    ) -> Vec<&'q q::Field<'q, &'q str>> {
+        let mut fields = Vec::new();
+
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if self.included(&field.directives) {
+                        fields.push(field);
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    if !self.included(&spread.directives) {
+                        continue;
+                    }
+                    if let Some(fragment) = self.fragments.get(spread.fragment_name) {
+                        let TypeCondition::On(condition) = fragment.type_condition;
+                        if self.schema.applies(type_name, condition) {
+                            fields.extend(self.collect_fields(type_name, &fragment.selection_set));
+                        }
+                    }
+                }
+                Selection::InlineFragment(inline) => {
+                    if !self.included(&inline.directives) {
+                        continue;
+                    }
+                    let applies = match inline.type_condition {
+                        Some(TypeCondition::On(condition)) => self.schema.applies(type_name, condition),
+                        None => true,
+                    };
+                    if applies {
+                        fields.extend(self.collect_fields(type_name, &inline.selection_set));
+                    }
+                }
+            }
+        }
+
+        fields
+    }
+
+    fn included(&self, directives: &[q::Directive<'q, &'q str>]) -> bool {
+        directives.iter().all(|directive| {
+            let condition = directive
+                .arguments
+                .iter()
+                .find(|(name, _)| *name == "if")
+                .map(|(_, value)| to_json(value, self.variables))
+                .and_then(|value| value.as_bool());
+            match directive.name {
+                "skip" => condition != Some(true),
+                "include" => condition != Some(false),
+                _ => true,
+            }
+        })
+    }
+
+    fn argument(&self, field: &q::Field<'q, &'q str>, name: &str) -> Option<Value> {
+        field
+            .arguments
+            .iter()
+            .find(|(argument, _)| *argument == name)
+            .map(|(_, value)| to_json(value, self.variables))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+
+    fn fake_scalar(&mut self, type_name: &str, field_name: &str) -> Value {
+        let n = self.next();
+        let field = field_name.to_ascii_lowercase();
+
+        match type_name {
+            "Int" => json!(n),
+            "Float" => json!(n as f64 + 0.5),
+            "Boolean" => json!(n % 2 == 1),
+            "ID" => json!(n.to_string()),
+            _ if field.contains("email") => json!(format!("user{}@example.com", n)),
+            _ if field.contains("url") => json!(format!("https://example.com/{}", n)),
+            _ if field.ends_with("at") || field.contains("date") => json!(chrono::Utc::now().to_rfc3339()),
+            _ if field.contains("name") => json!(format!("{} {}", capitalize(field_name), n)),
+            _ => json!(format!("{} {}", field_name, n)),
+        }
+    }
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn to_json<'q>(value: &q::Value<'q, &'q str>, variables: &Value) -> Value {
+    match value {
+        q::Value::Variable(name) => variables.get(name).cloned().unwrap_or(Value::Null),
+        q::Value::Int(number) => number.as_i64().map(Value::from).unwrap_or(Value::Null),
+        q::Value::Float(number) => json!(number),
+        q::Value::String(string) => Value::String(string.clone()),
+        q::Value::Boolean(boolean) => Value::Bool(*boolean),
+        q::Value::Null => Value::Null,
+        q::Value::Enum(name) => Value::String(name.to_string()),
+        q::Value::List(items) => items.iter().map(|item| to_json(item, variables)).collect(),
+        q::Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), to_json(value, variables)))
+                .collect(),
+        ),
+    }
+}
+
+/// Projects a selection set onto plain JSON, used to answer introspection queries.
+fn resolve_json<'q>(
+    value: &Value,
+    selection_set: &SelectionSet<'q, &'q str>,
+    fragments: &HashMap<&'q str, &'q q::FragmentDefinition<'q, &'q str>>,
+) -> Value {
+    match value {
+        Value::Array(items) => items.iter().map(|item| resolve_json(item, selection_set, fragments)).collect(),
+        Value::Object(object) => {
+            let mut result = Map::new();
+            collect_json_fields(object, selection_set, fragments, &mut result);
+            Value::Object(result)
+        }
+        other => other.clone(),
+    }
+}
+
+fn collect_json_fields<'q>(
+    object: &Map<String, Value>,
+    selection_set: &SelectionSet<'q, &'q str>,
+    fragments: &HashMap<&'q str, &'q q::FragmentDefinition<'q, &'q str>>,
+    result: &mut Map<String, Value>,
+) {
+    for selection in &selection_set.items {
+        match selection {
+            Selection::Field(field) => {
+                let value = object.get(field.name).cloned().unwrap_or(Value::Null);
+                let key = field.alias.unwrap_or(field.name).to_string();
+                result.insert(key, resolve_json(&value, &field.selection_set, fragments));
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = fragments.get(spread.fragment_name) {
+                    collect_json_fields(object, &fragment.selection_set, fragments, result);
+                }
+            }
+            Selection::InlineFragment(inline) => {
+                collect_json_fields(object, &inline.selection_set, fragments, result);
+            }
+        }
+    }
+}
+
+fn introspect_schema(schema: &Schema) -> Value {
+    let mut type_names: Vec<String> = schema.types.keys().map(|name| name.to_string()).collect();
+    type_names.extend(
+        BUILTIN_SCALARS
+            .iter()
+            .filter(|scalar| !schema.types.contains_key(**scalar))
+            .map(|scalar| scalar.to_string()),
+    );
+    type_names.sort();
+
+    let root = |name: &Option<String>| match name {
+        Some(name) => json!({ "__typename": "__Type", "kind": "OBJECT", "name": name }),
+        None => Value::Null,
+    };
+
+    let mut directives: Vec<Value> = schema
+        .directives
+        .iter()
+        .map(|directive| {
+            json!({
+                "__typename": "__Directive",
+                "name": directive.name,
+                "description": directive.description,
+                "isRepeatable": directive.repeatable,
+                "locations": directive.locations.iter().map(|location| location.as_str()).collect::<Vec<_>>(),
+                "args": directive.arguments.iter().map(|input| introspect_input_value(schema, input)).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    for name in ["skip", "include"] {
+        directives.push(json!({
+            "__typename": "__Directive",
+            "name": name,
+            "description": null,
+            "isRepeatable": false,
+            "locations": ["FIELD", "FRAGMENT_SPREAD", "INLINE_FRAGMENT"],
+            "args": [{
+                "__typename": "__InputValue",
+                "name": "if",
+                "description": null,
+                "type": type_ref_named("NON_NULL", None, Some(type_ref_named("SCALAR", Some("Boolean"), None))),
+                "defaultValue": null,
+                "isDeprecated": false,
+                "deprecationReason": null,
+            }],
+        }));
+    }
+
+    json!({
+        "__typename": "__Schema",
+        "description": null,
+        "queryType": root(&Some(schema.query_type.clone())),
+        "mutationType": root(&schema.mutation_type),
+        "subscriptionType": root(&schema.subscription_type),
+        "types": type_names.iter().map(|name| introspect_type_named(schema, name)).collect::<Vec<_>>(),
+        "directives": directives,
+    })
+}
+
+fn introspect_type_named(schema: &Schema, name: &str) -> Value {
+    let Some(definition) = schema.types.get(name) else {
+        return introspect_type("SCALAR", name, None, json!({}));
+    };
+
+    match definition {
+        TypeDefinition::Scalar(scalar) => introspect_type("SCALAR", name, scalar.description.as_deref(), json!({})),
+        TypeDefinition::Object(object) => introspect_type(
+            "OBJECT",
+            name,
+            object.description.as_deref(),
+            json!({
+                "fields": object.fields.iter().map(|field| introspect_field(schema, field)).collect::<Vec<_>>(),
+                "interfaces": object.implements_interfaces.iter().map(|i| type_ref_named("INTERFACE", Some(i), None)).collect::<Vec<_>>(),
+            }),
+        ),
+        TypeDefinition::Interface(interface) => introspect_type(
+            "INTERFACE",
+            name,
+            interface.description.as_deref(),
+            json!({
+                "fields": interface.fields.iter().map(|field| introspect_field(schema, field)).collect::<Vec<_>>(),
+                "interfaces": interface.implements_interfaces.iter().map(|i| type_ref_named("INTERFACE", Some(i), None)).collect::<Vec<_>>(),
+                "possibleTypes": schema.possible_types(name).iter().map(|t| type_ref_named("OBJECT", Some(t), None)).collect::<Vec<_>>(),
+            }),
+        ),
+        TypeDefinition::Union(union) => introspect_type(
+            "UNION",
+            name,
+            union.description.as_deref(),
+            json!({
+                "possibleTypes": union.types.iter().map(|t| type_ref_named("OBJECT", Some(t), None)).collect::<Vec<_>>(),
+            }),
+        ),
+        TypeDefinition::Enum(enumeration) => introspect_type(
+            "ENUM",
+            name,
+            enumeration.description.as_deref(),
+            json!({
+                "enumValues": enumeration.values.iter().map(|value| {
+                    let deprecation = deprecation_reason(&value.directives);
+                    json!({
+                        "__typename": "__EnumValue",
+                        "name": value.name,
+                        "description": value.description,
+                        "isDeprecated": deprecation.is_some(),
+                        "deprecationReason": deprecation,
+                    })
+                }).collect::<Vec<_>>(),
+            }),
+        ),
+        TypeDefinition::InputObject(input) => introspect_type(
+            "INPUT_OBJECT",
+            name,
+            input.description.as_deref(),
+            json!({
+                "inputFields": input.fields.iter().map(|input| introspect_input_value(schema, input)).collect::<Vec<_>>(),
+            }),
+        ),
+    }
+}
+
+fn introspect_type(kind: &str, name: &str, description: Option<&str>, extra: Value) -> Value {
+    let mut introspected = json!({
+        "__typename": "__Type",
+        "kind": kind,
+        "name": name,
+        "description": description,
+        "specifiedByURL": null,
+        "fields": null,
+        "interfaces": null,
+        "possibleTypes": null,
+        "enumValues": null,
+        "inputFields": null,
+        "ofType": null,
+    });
+    if let (Value::Object(target), Value::Object(extra)) = (&mut introspected, extra) {
+        target.extend(extra);
+    }
+    introspected
+}
+
+fn introspect_field(schema: &Schema, field: &s::Field<'static, String>) -> Value {
+    let deprecation = deprecation_reason(&field.directives);
+    json!({
+        "__typename": "__Field",
+        "name": field.name,
+        "description": field.description,
+        "args": field.arguments.iter().map(|input| introspect_input_value(schema, input)).collect::<Vec<_>>(),
+        "type": introspect_type_ref(schema, &field.field_type),
+        "isDeprecated": deprecation.is_some(),
+        "deprecationReason": deprecation,
+    })
+}
+
+fn introspect_input_value(schema: &Schema, input: &s::InputValue<'static, String>) -> Value {
+    json!({
+        "__typename": "__InputValue",
+        "name": input.name,
+        "description": input.description,
+        "type": introspect_type_ref(schema, &input.value_type),
+        "defaultValue": input.default_value.as_ref().map(|value| value.to_string()),
+        "isDeprecated": false,
+        "deprecationReason": null,
+    })
+}
+
+fn introspect_type_ref(schema: &Schema, field_type: &s::Type<'static, String>) -> Value {
+    match field_type {
+        s::Type::NonNullType(inner) => type_ref_named("NON_NULL", None, Some(introspect_type_ref(schema, inner))),
+        s::Type::ListType(inner) => type_ref_named("LIST", None, Some(introspect_type_ref(schema, inner))),
+        s::Type::NamedType(name) => type_ref_named(schema.kind_of(name), Some(name), None),
+    }
+}
+
+fn type_ref_named(kind: &str, name: Option<&str>, of_type: Option<Value>) -> Value {
+    json!({
+        "__typename": "__Type",
+        "kind": kind,
+        "name": name,
+        "ofType": of_type,
+    })
+}
+
+fn deprecation_reason(directives: &[s::Directive<'static, String>]) -> Option<String> {
+    let directive = directives.iter().find(|directive| directive.name == "deprecated")?;
+    let reason = directive
+        .arguments
+        .iter()
+        .find(|(name, _)| *name == "reason")
+        .and_then(|(_, value)| match value {
+            s::Value::String(reason) => Some(reason.clone()),
+            _ => None,
+        });
+    Some(reason.unwrap_or_else(|| "No longer supported".to_string()))
+}
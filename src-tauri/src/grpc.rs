@@ -0,0 +1,211 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures_util::stream;
+use http_body::Frame;
+use http_body_util::StreamBody;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor};
+use protobuf::Message as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::time::{sleep, Duration};
+
+use crate::models::{Collection, Route};
+
+const GRPC_OK: u32 = 0;
+const GRPC_INTERNAL: u32 = 13;
+const GRPC_UNIMPLEMENTED: u32 = 12;
+
+/// `grpc-message` keeps printable ASCII as-is and percent-encodes everything else.
+const GRPC_MESSAGE_ENCODE: &AsciiSet = &CONTROLS.add(b'%');
+
+/// What a pool was parsed from: the collection's `asset_dir`, its `.proto` files and
+/// when each was last modified.
+type PoolKey = (Option<String>, Vec<String>, Vec<Option<SystemTime>>);
+
+/// The descriptor pool of a mock server's collection, kept until its `.proto` files
+/// change, so calls don't reparse them.
+#[derive(Clone, Default)]
+pub struct DescriptorCache {
+    pool: Arc<Mutex<Option<(PoolKey, DescriptorPool)>>>,
+}
+
+impl DescriptorCache {
+    /// The collection's descriptor pool, parsed again when its `asset_dir`, its list of
+    /// `.proto` files or one of those files has changed since last time. Imported
+    /// files are not watched.
+    pub fn get(&self, collection: &Collection) -> Result<DescriptorPool, String> {
+        let modified = input_paths(collection)
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+            .collect();
+        let key = (collection.asset_dir.clone(), collection.proto_files.clone(), modified);
+
+        let mut cached = self.pool.lock().unwrap();
+        if let Some((ref cached_key, ref pool)) = *cached {
+            if *cached_key == key {
+                return Ok(pool.clone());
+            }
+        }
+
+        let pool = load_descriptors(collection)?;
+        *cached = Some((key, pool.clone()));
+        Ok(pool)
+    }
+}
+
+fn input_paths(collection: &Collection) -> Vec<PathBuf> {
+    let base = collection.asset_dir.as_ref().map(PathBuf::from).unwrap_or_default();
+    collection.proto_files.iter().map(|file| base.join(file)).collect()
+}
+
+/// Parses the collection's `.proto` files, resolving relative paths and imports
+/// against its `asset_dir`.
+pub fn load_descriptors(collection: &Collection) -> Result<DescriptorPool, String> {
+    if collection.proto_files.is_empty() {
+        return Err("Collection has no .proto files".to_string());
+    }
+
+    let inputs = input_paths(collection);
+
+    // Include directories in order of precedence, each once
+    let mut includes: Vec<PathBuf> = collection.asset_dir.iter().map(PathBuf::from).collect();
+    for dir in inputs.iter().filter_map(|input| input.parent().map(Path::to_path_buf)) {
+        if !includes.contains(&dir) {
+            includes.push(dir);
+        }
+    }
+
+    let file_descriptor_set = protobuf_parse::Parser::new()
+        .pure()
+        .includes(&includes)
+        .inputs(&inputs)
+        .file_descriptor_set()
+        .map_err(|e| format!("Failed to parse .proto files: {:#}", e))?;
+    let bytes = file_descriptor_set
+        .write_to_bytes()
+        .map_err(|e| format!("Failed to encode descriptors: {}", e))?;
+
+    DescriptorPool::decode(bytes.as_slice()).map_err(|e| format!("Invalid descriptors: {}", e))
+}
+
+/// Request paths (`/package.Service/Method`) of every RPC in the pool.
+pub fn rpc_paths(pool: &DescriptorPool) -> Vec<String> {
+    pool.services()
+        .flat_map(|service| {
+            service
+                .methods()
+                .map(|method| format!("/{}/{}", service.full_name(), method.name()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn find_method(pool: &DescriptorPool, path: &str) -> Option<MethodDescriptor> {
+    let (service, method) = path.trim_start_matches('/').split_once('/')?;
+    pool.get_service_by_name(service)?
+        .methods()
+        .find(|candidate| candidate.name() == method)
+}
+
+/// Answers a gRPC call with the route's canned messages, encoded as the RPC's output
+/// type, followed by its configured status. Server-streaming RPCs get every message in
+/// turn; unary ones only the first.
+pub async fn respond(
+    collection: &Collection,
+    descriptors: &DescriptorCache,
+    route: Option<&Route>,
+    path: &str,
+) -> Response {
+    let Some(route) = route else {
+        return status_only(GRPC_UNIMPLEMENTED, &format!("No mock configured for {}", path));
+    };
+
+    let pool = match descriptors.get(collection) {
+        Ok(pool) => pool,
+        Err(e) => return status_only(GRPC_INTERNAL, &e),
+    };
+    let Some(method) = find_method(&pool, path) else {
+        return status_only(GRPC_UNIMPLEMENTED, &format!("{} is not defined in the .proto files", path));
+    };
+
+    let config = route.grpc.clone().unwrap_or_default();
+    let mut messages = config.messages.clone();
+    if messages.is_empty() && config.status == GRPC_OK && !method.is_server_streaming() {
+        messages.push(serde_json::json!({}));
+    }
+    if !method.is_server_streaming() {
+        messages.truncate(1);
+    }
+
+    let mut frames = Vec::with_capacity(messages.len());
+    for message in &messages {
+        match encode_message(&method, message) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => return status_only(GRPC_INTERNAL, &e),
+        }
+    }
+
+    if frames.is_empty() {
+        return status_only(config.status, config.status_message.as_deref().unwrap_or(""));
+    }
+
+    let trailers = status_headers(config.status, config.status_message.as_deref().unwrap_or(""));
+    let interval = Duration::from_millis(config.stream_interval_ms);
+
+    let body = stream::unfold((frames.into_iter(), Some(trailers), true), move |(mut frames, trailers, first)| async move {
+        match frames.next() {
+            Some(frame) => {
+                if !first && !interval.is_zero() {
+                    sleep(interval).await;
+                }
+                Some((Ok::<_, std::io::Error>(Frame::data(frame)), (frames, trailers, false)))
+            }
+            None => trailers.map(|trailers| (Ok(Frame::trailers(trailers)), (frames, None, false))),
+        }
+    });
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/grpc")],
+        Body::new(StreamBody::new(body)),
+    )
+        .into_response()
+}
+
+/// Encodes JSON as the method's output message, with the gRPC length prefix.
+fn encode_message(method: &MethodDescriptor, message: &serde_json::Value) -> Result<Bytes, String> {
+    let message = DynamicMessage::deserialize(method.output(), message)
+        .map_err(|e| format!("Response does not match {}: {}", method.output().full_name(), e))?;
+    let encoded = message.encode_to_vec();
+
+    let mut frame = Vec::with_capacity(encoded.len() + 5);
+    frame.push(0);
+    frame.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&encoded);
+    Ok(Bytes::from(frame))
+}
+
+/// A "Trailers-Only" response, carrying the status in the headers with no body.
+fn status_only(status: u32, message: &str) -> Response {
+    let mut response = (StatusCode::OK, [(header::CONTENT_TYPE, "application/grpc")]).into_response();
+    response.headers_mut().extend(status_headers(status, message));
+    response
+}
+
+fn status_headers(status: u32, message: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("grpc-status", HeaderValue::from(status));
+    if !message.is_empty() {
+        let encoded = utf8_percent_encode(message, GRPC_MESSAGE_ENCODE).to_string();
+        if let Ok(value) = HeaderValue::from_str(&encoded) {
+            headers.insert("grpc-message", value);
+        }
+    }
+    headers
+}
@@ -0,0 +1,77 @@
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// How many requests a mock server remembers; the oldest are dropped first.
+const JOURNAL_CAPACITY: usize = 1000;
+
+/// A request received by a mock server, with the route that answered it.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub received_at: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: serde_json::Map<String, serde_json::Value>,
+    /// The request body, decoded lossily as UTF-8.
+    pub body: String,
+    /// The route that matched, if any.
+    pub route_id: Option<String>,
+    pub status_code: u16,
+}
+
+impl JournalEntry {
+    pub fn new(
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: &[u8],
+        route_id: Option<String>,
+        status: StatusCode,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            received_at: Utc::now(),
+            method: method.to_string(),
+            path: uri.path().to_string(),
+            query: uri.query().map(str::to_string),
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    (name.to_string(), serde_json::Value::String(value.to_str().unwrap_or_default().to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(body).into_owned(),
+            route_id,
+            status_code: status.as_u16(),
+        }
+    }
+}
+
+/// The most recent requests received by a mock server, oldest first.
+#[derive(Clone, Default)]
+pub struct Journal {
+    entries: Arc<Mutex<VecDeque<JournalEntry>>>,
+}
+
+impl Journal {
+    pub fn record(&self, entry: JournalEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == JOURNAL_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
@@ -3,10 +3,33 @@
     windows_subsystem = "windows"
 )]
 
+mod admin;
 mod api;
+mod auth;
+mod compression;
+mod content_type;
+mod contract;
+mod cookies;
+mod cors;
 mod db;
+mod fake;
+mod fallback;
+mod graphql;
+mod grpc;
+mod journal;
+mod matching;
 mod mock_server;
 mod models;
+mod oidc;
+mod rate_limit;
+mod request;
+mod route_cache;
+mod session;
+mod sse;
+mod static_files;
+mod template;
+mod validation;
+mod websocket;
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -44,6 +67,7 @@ async fn main() {
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             create_collection,
+            create_oidc_collection,
             get_collections,
             update_collection,
             delete_collection,
@@ -51,9 +75,17 @@ async fn main() {
             get_routes,
             update_route,
             delete_route,
+            import_proto_services,
+            check_contract,
+            generate_route_body,
             start_server,
             stop_server,
             get_running_servers,
+            push_websocket_message,
+            get_rate_limits,
+            reset_rate_limits,
+            get_sessions,
+            clear_sessions,
             test_route
         ])
         .run(context)
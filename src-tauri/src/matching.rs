@@ -0,0 +1,312 @@
+use percent_encoding::percent_decode_str;
+use regex::{Regex, RegexBuilder};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::models::{BodyMatcher, FieldMatch, FieldMatcher, PathMatch, PathNormalization, RequestMatch, Route};
+use crate::template;
+
+/// Whether `actual` contains everything in `expected`: objects may have extra fields,
+/// and each expected array element must match some element of the actual array.
+pub fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|a| json_contains(a, value))),
+        (Value::Array(actual), Value::Array(expected)) => expected
+            .iter()
+            .all(|value| actual.iter().any(|a| json_contains(a, value))),
+        _ => actual == expected,
+    }
+}
+
+/// A route's regular expressions, compiled once rather than for every request.
+pub struct RoutePatterns {
+    /// A regex route's path, as matched with and without regard to letter case.
+    path: Option<(Regex, Regex)>,
+    /// The patterns of the route's `request_match`, by their source.
+    rules: HashMap<String, Regex>,
+}
+
+impl RoutePatterns {
+    pub fn compile(route: &Route) -> Result<Self, String> {
+        let path = match route.path_match {
+            PathMatch::Regex => {
+                let build = |ignore_case| {
+                    RegexBuilder::new(&format!("^(?:{})$", route.path))
+                        .case_insensitive(ignore_case)
+                        .build()
+                        .map_err(|e| format!("Invalid path pattern: {}", e))
+                };
+                Some((build(false)?, build(true)?))
+            }
+            PathMatch::Exact | PathMatch::Template => None,
+        };
+
+        let mut rules = HashMap::new();
+        for pattern in route.request_match.iter().flat_map(rule_patterns) {
+            let re = Regex::new(pattern).map_err(|e| format!("Invalid match pattern {}: {}", pattern, e))?;
+            rules.insert(pattern.clone(), re);
+        }
+
+        Ok(Self { path, rules })
+    }
+}
+
+/// Whether a request satisfies every condition of a route's `RequestMatch`, using the
+/// route's compiled `patterns`. `request` is the template request context, which
+/// carries the decoded query and lowercased headers.
+pub fn request_matches(rules: &RequestMatch, patterns: &RoutePatterns, request: &Value, body: &[u8]) -> bool {
+    let headers = &request["headers"];
+    let fields_match = |fields: &[FieldMatch], values: &Value, lowercase: bool| {
+        fields.iter().all(|field| {
+            let name = if lowercase { field.name.to_ascii_lowercase() } else { field.name.clone() };
+            field_matches(&field.matcher, patterns, values.get(&name).and_then(Value::as_str))
+        })
+    };
+
+    if !fields_match(&rules.query, &request["query"], false)
+        || !fields_match(&rules.headers, headers, true)
+        || !fields_match(&rules.cookies, &request["cookies"], false)
+    {
+        return false;
+    }
+
+    if !rules.form.is_empty() {
+        let is_form = headers["content-type"]
+            .as_str()
+            .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"));
+        let form = if is_form {
+            Value::Object(template::parse_urlencoded(&String::from_utf8_lossy(body)))
+        } else {
+            Value::Null
+        };
+        if !fields_match(&rules.form, &form, false) {
+            return false;
+        }
+    }
+
+    rules.body.as_ref().is_none_or(|matcher| body_matches(matcher, patterns, body))
+}
+
+fn field_matches(matcher: &FieldMatcher, patterns: &RoutePatterns, value: Option<&str>) -> bool {
+    match (matcher, value) {
+        (FieldMatcher::Absent, value) => value.is_none(),
+        (_, None) => false,
+        (FieldMatcher::Present, Some(_)) => true,
+        (FieldMatcher::Exact(expected), Some(value)) => value == expected,
+        (FieldMatcher::Regex(pattern), Some(value)) => patterns.rules.get(pattern).is_some_and(|re| re.is_match(value)),
+    }
+}
+
+fn body_matches(matcher: &BodyMatcher, patterns: &RoutePatterns, body: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(body);
+    let json = || serde_json::from_slice::<Value>(body).ok();
+
+    match matcher {
+        BodyMatcher::Exact(expected) => text == expected.as_str(),
+        BodyMatcher::Contains(needle) => text.contains(needle.as_str()),
+        BodyMatcher::Regex(pattern) => patterns.rules.get(pattern).is_some_and(|re| re.is_match(&text)),
+        BodyMatcher::JsonEquals(expected) => json().is_some_and(|actual| &actual == expected),
+        BodyMatcher::JsonPartial(expected) => json().is_some_and(|actual| json_contains(&actual, expected)),
+    }
+}
+
+/// Rejects match rules with regular expressions that don't compile.
+pub fn check_request_match(rules: &RequestMatch) -> Result<(), String> {
+    for pattern in rule_patterns(rules) {
+        Regex::new(pattern).map_err(|e| format!("Invalid match pattern {}: {}", pattern, e))?;
+    }
+    Ok(())
+}
+
+/// The regular expressions among a route's match rules.
+fn rule_patterns(rules: &RequestMatch) -> impl Iterator<Item = &String> {
+    let field_patterns = rules.query.iter().chain(&rules.headers).chain(&rules.form).chain(&rules.cookies).filter_map(|field| match field.matcher {
+        FieldMatcher::Regex(ref pattern) => Some(pattern),
+        _ => None,
+    });
+    let body_pattern = match rules.body {
+        Some(BodyMatcher::Regex(ref pattern)) => Some(pattern),
+        _ => None,
+    };
+    field_patterns.chain(body_pattern)
+}
+
+/// Compares a request path with a route's path under a collection's normalization
+/// settings, returning the params the route captured, or `None` when it doesn't match.
+/// Exact and template paths are split into segments before anything is decoded, so an
+/// encoded `%2F` never splits a segment. Regex routes are matched against the whole
+/// normalized request path, decoded once when `decode_percent` is set, with the regex
+/// from the route's compiled `patterns`.
+pub fn match_path(
+    options: &PathNormalization,
+    route: &Route,
+    patterns: &RoutePatterns,
+    path: &str,
+) -> Option<Map<String, Value>> {
+    let pattern = route.path.as_str();
+    let path = normalize_path(options, path);
+    let ignore_case = options.case_insensitive;
+    let same = |a: &str, b: &str| if ignore_case { a.eq_ignore_ascii_case(b) } else { a == b };
+
+    match route.path_match {
+        PathMatch::Exact => {
+            let pattern = normalize_path(options, pattern);
+            let parts = segments(&pattern, options.decode_percent);
+            let segments = segments(&path, options.decode_percent);
+            let matches = parts.len() == segments.len() && parts.iter().zip(&segments).all(|(a, b)| same(a, b));
+            matches.then(Map::new)
+        }
+        PathMatch::Template => match_template(&normalize_path(options, pattern), &path, options.decode_percent, same),
+        PathMatch::Regex => {
+            let path = if options.decode_percent { decode_segment(&path) } else { path };
+            let (exact_case, any_case) = patterns.path.as_ref()?;
+            let re = if ignore_case { any_case } else { exact_case };
+            let captures = re.captures(&path)?;
+
+            let mut params = Map::new();
+            for (index, name) in re.capture_names().enumerate().skip(1) {
+                if let Some(capture) = captures.get(index) {
+                    let value = Value::String(capture.as_str().to_string());
+                    if let Some(name) = name {
+                        params.insert(name.to_string(), value.clone());
+                    }
+                    params.insert(index.to_string(), value);
+                }
+            }
+            Some(params)
+        }
+    }
+}
+
+/// Params are always decoded, once; literal segments only when `decode` is set.
+fn match_template(
+    pattern: &str,
+    path: &str,
+    decode: bool,
+    same: impl Fn(&str, &str) -> bool,
+) -> Option<Map<String, Value>> {
+    let parts: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let mut params = Map::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        match template_param(part) {
+            Some(name) if name.ends_with('*') => {
+                let rest = segments.get(index..)?.join("/");
+                params.insert(name.trim_end_matches('*').to_string(), Value::String(decode_segment(&rest)));
+                return Some(params);
+            }
+            Some(name) => {
+                let segment = segments.get(index).filter(|segment| !segment.is_empty())?;
+                params.insert(name.to_string(), Value::String(decode_segment(segment)));
+            }
+            None => {
+                let segment = segments.get(index)?;
+                let matches = if decode {
+                    same(&decode_segment(segment), &decode_segment(part))
+                } else {
+                    same(segment, part)
+                };
+                if !matches {
+                    return None;
+                }
+            }
+        }
+    }
+
+    (parts.len() == segments.len()).then_some(params)
+}
+
+/// Applies a collection's slash settings to a request or route path. Letter case and
+/// percent-encoding are left alone; `match_path` compares case-insensitively and
+/// decodes segment by segment instead, so captured params keep the case they were
+/// sent in and nothing is decoded twice.
+pub fn normalize_path(options: &PathNormalization, path: &str) -> String {
+    let mut path = path.to_string();
+
+    if options.collapse_slashes {
+        while path.contains("//") {
+            path = path.replace("//", "/");
+        }
+    }
+    if options.ignore_trailing_slash && path.len() > 1 && path.ends_with('/') {
+        let trimmed = path.trim_end_matches('/');
+        path = if trimmed.is_empty() { "/".to_string() } else { trimmed.to_string() };
+    }
+
+    path
+}
+
+/// The param name of a `{name}` or `:name` template segment.
+fn template_param(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix(':')
+        .or_else(|| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+fn decode_segment(segment: &str) -> String {
+    percent_decode_str(segment).decode_utf8_lossy().into_owned()
+}
+
+fn segments(path: &str, decode: bool) -> Vec<String> {
+    path.trim_start_matches('/')
+        .split('/')
+        .map(|segment| if decode { decode_segment(segment) } else { segment.to_string() })
+        .collect()
+}
+
+/// A path that `path` matches, for sending test requests: template params are filled
+/// with `1`. Regex routes have no path to derive, so they can't be tested this way.
+pub fn sample_path(path_match: PathMatch, path: &str) -> Result<String, String> {
+    match path_match {
+        PathMatch::Exact => Ok(path.to_string()),
+        PathMatch::Template => Ok(path
+            .split('/')
+            .map(|segment| if template_param(segment).is_some() { "1" } else { segment })
+            .collect::<Vec<_>>()
+            .join("/")),
+        PathMatch::Regex => Err("Regex routes can't be tested without a sample path".to_string()),
+    }
+}
+
+/// Rejects route paths that can't be matched as their `PathMatch` says.
+pub fn check_path(path_match: PathMatch, path: &str) -> Result<(), String> {
+    match path_match {
+        PathMatch::Exact => Ok(()),
+        PathMatch::Template => {
+            let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+            let mut names = Vec::new();
+
+            for (index, part) in parts.iter().enumerate() {
+                match template_param(part) {
+                    Some(name) => {
+                        let (name, rest) = match name.strip_suffix('*') {
+                            Some(name) => (name, true),
+                            None => (name, false),
+                        };
+                        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                            return Err(format!("Invalid path parameter name in segment {}", part));
+                        }
+                        if rest && index != parts.len() - 1 {
+                            return Err(format!("{} must be the last segment of the path", part));
+                        }
+                        if names.contains(&name) {
+                            return Err(format!("Path parameter {} appears more than once", name));
+                        }
+                        names.push(name);
+                    }
+                    None if part.contains(['{', '}']) => {
+                        return Err(format!("Path segment {} must be a whole {{name}} parameter", part));
+                    }
+                    None => {}
+                }
+            }
+            Ok(())
+        }
+        PathMatch::Regex => Regex::new(&format!("^(?:{})$", path))
+            .map(|_| ())
+            .map_err(|e| format!("Invalid path pattern: {}", e)),
+    }
+}
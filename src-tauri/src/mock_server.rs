@@ -1,57 +1,161 @@
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, Method, StatusCode},
+    body::{Body, Bytes},
+    extract::{State, WebSocketUpgrade},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
     routing::any,
     Router,
 };
+use futures_util::stream;
+use jsonschema::Validator;
+use log::warn;
 use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
 use tokio::time::{sleep, Duration};
-use tower_http::cors::CorsLayer;
 
+use crate::admin;
+use crate::auth;
+use crate::compression;
+use crate::content_type;
+use crate::contract::{self, SpecCache};
+use crate::cookies;
+use crate::cors;
 use crate::db::Database;
-use crate::models::HttpMethod;
+use crate::fake::Faker;
+use crate::fallback;
+use crate::graphql;
+use crate::grpc::{self, DescriptorCache};
+use crate::journal::{Journal, JournalEntry};
+use crate::matching::{self, RoutePatterns};
+use crate::models::{BodySource, Collection, CollectionKind, HttpMethod, Representation, ResponseHeader, ResponseHeaders, Route, RouteKind};
+use crate::oidc::{self, OidcSessions};
+use crate::rate_limit::{self, RateLimitCounter, RateLimiter};
+use crate::request::RequestContext;
+use crate::route_cache::RouteCache;
+use crate::session::{Session, SessionStore};
+use crate::sse;
+use crate::static_files;
+use crate::template;
+use crate::validation;
+use crate::websocket::{self, WebSocketBroadcast};
 
 pub struct MockServer {
     port: u16,
     collection_id: String,
     shutdown_tx: Option<oneshot::Sender<()>>,
+    websocket_tx: broadcast::Sender<WebSocketBroadcast>,
+    journal: Journal,
+    oidc_sessions: OidcSessions,
+    rate_limiter: RateLimiter,
+    sessions: SessionStore,
+    descriptors: DescriptorCache,
+    specs: SpecCache,
+    graphql_schemas: RouteCache<graphql::Schema>,
+    route_patterns: RouteCache<RoutePatterns>,
+    validators: RouteCache<Validator>,
 }
 
 impl MockServer {
     pub fn new(port: u16, collection_id: String) -> Self {
+        let (websocket_tx, _) = broadcast::channel(64);
+
         Self {
             port,
             collection_id,
             shutdown_tx: None,
+            websocket_tx,
+            journal: Journal::default(),
+            oidc_sessions: OidcSessions::default(),
+            rate_limiter: RateLimiter::default(),
+            sessions: SessionStore::default(),
+            descriptors: DescriptorCache::default(),
+            specs: SpecCache::default(),
+            graphql_schemas: RouteCache::default(),
+            route_patterns: RouteCache::default(),
+            validators: RouteCache::default(),
         }
     }
 
     pub async fn start(&mut self, db: Database) -> Result<(), String> {
+        let listener = tokio::net::TcpListener::bind(self.addr())
+            .await
+            .map_err(|e| format!("Failed to bind port {}: {}", self.port, e))?;
+        self.serve(db, listener).await
+    }
+
+    /// Stops the server and starts it again on the same port, so settings read at
+    /// start take effect. The journal, sessions and rate limit counters carry over.
+    pub async fn restart(&mut self, db: Database) -> Result<(), String> {
+        self.stop();
+
+        // The old server lets go of the port as soon as it sees the shutdown signal
+        let mut attempts = 0;
+        let listener = loop {
+            match tokio::net::TcpListener::bind(self.addr()).await {
+                Ok(listener) => break listener,
+                Err(_) if attempts < 20 => {
+                    attempts += 1;
+                    sleep(Duration::from_millis(50)).await;
+                }
+                Err(e) => return Err(format!("Failed to bind port {}: {}", self.port, e)),
+            }
+        };
+        self.serve(db, listener).await
+    }
+
+    fn addr(&self) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], self.port))
+    }
+
+    async fn serve(&mut self, db: Database, listener: tokio::net::TcpListener) -> Result<(), String> {
+        let collection = db.get_collection(&self.collection_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("Collection not found")?;
+        let cors = cors::layer(&collection.cors)?;
+
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
         self.shutdown_tx = Some(shutdown_tx);
 
         let app_state = Arc::new(MockServerState {
             db,
             collection_id: self.collection_id.clone(),
+            websocket_tx: self.websocket_tx.clone(),
+            journal: self.journal.clone(),
+            oidc_sessions: self.oidc_sessions.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            sessions: self.sessions.clone(),
+            descriptors: self.descriptors.clone(),
+            specs: self.specs.clone(),
+            graphql_schemas: self.graphql_schemas.clone(),
+            route_patterns: self.route_patterns.clone(),
+            validators: self.validators.clone(),
         });
 
-        let app = Router::new()
-            .route("/*path", any(handle_mock_request))
-            .route("/", any(handle_mock_request))
-            .layer(CorsLayer::permissive())
-            .with_state(app_state);
+        // Every other path, the root included, goes to the one handler
+        let mut app = Router::new().fallback(handle_mock_request);
+        if let Some(ref config) = collection.compression {
+            app = compression::apply(app, config);
+        }
+        if let Some(cors) = cors {
+            app = app.layer(cors);
+        }
+
+        // The admin API sits outside the collection's CORS policy, so web pages the user
+        // visits can't drive it from their browser. When it's off, `/__mocify` paths are
+        // ordinary routes with the layers above.
+        if collection.admin_api {
+            let admin = Router::new()
+                .route(admin::ADMIN_PREFIX, any(handle_admin_request))
+                .route(&format!("{}/*path", admin::ADMIN_PREFIX), any(handle_admin_request));
+            app = admin.merge(app);
+        }
+        let app = app.with_state(app_state);
 
-        let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
-        
         tokio::spawn(async move {
-            let listener = tokio::net::TcpListener::bind(addr)
-                .await
-                .expect("Failed to bind");
-            
-            axum::serve(listener, app)
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
                 .with_graceful_shutdown(async {
                     shutdown_rx.await.ok();
                 })
@@ -63,86 +167,441 @@ impl MockServer {
     }
 
     pub fn stop(&mut self) {
+        // Upgraded WebSocket connections outlive graceful shutdown, so close them explicitly
+        let _ = self.websocket_tx.send(WebSocketBroadcast::Shutdown);
+
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
         }
     }
+
+    /// Sends a text frame to connected WebSocket clients, optionally only those on `path`.
+    pub fn push_websocket_message(&self, path: Option<String>, message: String) {
+        let _ = self.websocket_tx.send(WebSocketBroadcast::Message { path, message });
+    }
+
+    pub fn rate_limits(&self) -> Vec<RateLimitCounter> {
+        self.rate_limiter.counters()
+    }
+
+    /// Gives every client its full rate limit back.
+    pub fn reset_rate_limits(&self) {
+        self.rate_limiter.reset();
+    }
+
+    pub fn sessions(&self) -> Vec<Session> {
+        self.sessions.sessions()
+    }
+
+    /// Forgets every session, logging all clients out.
+    pub fn clear_sessions(&self) {
+        self.sessions.clear();
+    }
 }
 
 #[derive(Clone)]
 struct MockServerState {
     db: Database,
     collection_id: String,
+    websocket_tx: broadcast::Sender<WebSocketBroadcast>,
+    journal: Journal,
+    oidc_sessions: OidcSessions,
+    rate_limiter: RateLimiter,
+    sessions: SessionStore,
+    descriptors: DescriptorCache,
+    specs: SpecCache,
+    graphql_schemas: RouteCache<graphql::Schema>,
+    route_patterns: RouteCache<RoutePatterns>,
+    validators: RouteCache<Validator>,
+}
+
+/// Serves `/__mocify/*` with the admin API; mounted only when the collection has it on.
+async fn handle_admin_request(State(state): State<Arc<MockServerState>>, incoming: RequestContext) -> Response {
+    admin::handle(&state.db, &state.collection_id, &state.journal, &state.websocket_tx, &incoming).await
 }
 
 async fn handle_mock_request(
     State(state): State<Arc<MockServerState>>,
-    method: Method,
-    Path(path): Path<String>,
-    _headers: HeaderMap,
+    upgrade: Option<WebSocketUpgrade>,
+    incoming: RequestContext,
 ) -> Response {
-    let path = format!("/{}", path);
-    
+    let mut request = incoming.template_context();
+    let RequestContext { method, uri, path, query, headers, body, remote_addr, .. } = incoming;
+
+    let collection = match state.db.get_collection(&state.collection_id).await {
+        Ok(Some(collection)) => collection,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Collection not found").into_response(),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
+    };
+
     // Get all routes for this collection
-    let routes = match state.db.get_routes(&state.collection_id).await {
+    let mut routes = match state.db.get_routes(&state.collection_id).await {
         Ok(routes) => routes,
         Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
     };
+    // Routes for a specific method come before `ANY` routes otherwise equal to them
+    routes.sort_by_key(|route| {
+        (std::cmp::Reverse(route.priority), route.path_match, route.method == HttpMethod::Any)
+    });
 
     // Find matching route
-    let matching_route = routes.into_iter().find(|route| {
-        route.path == path && method_matches(&method, &route.method)
+    let matched = routes.iter().find_map(|route| {
+        if !method_matches(&method, &route.method) {
+            return None;
+        }
+        let patterns = match state.route_patterns.get(route, || RoutePatterns::compile(route)) {
+            Ok(patterns) => patterns,
+            Err(e) => {
+                warn!("Skipping route {} {}: {}", route.method.as_str(), route.path, e);
+                return None;
+            }
+        };
+        let params = matching::match_path(&collection.path_normalization, route, &patterns, &path)?;
+        route
+            .request_match
+            .as_ref()
+            .is_none_or(|rules| matching::request_matches(rules, &patterns, &request, &body))
+            .then(|| (route.clone(), params))
+    });
+    let matching_route = matched.map(|(route, params)| {
+        request["params"] = serde_json::Value::Object(params);
+        route
+    });
+
+    let route_id = matching_route.as_ref().map(|route| route.id.clone());
+
+    // Count the request against the route's rate limit, or failing that the collection's
+    let rate_limit = matching_route.as_ref().filter(|_| collection.kind == CollectionKind::Http).and_then(|route| {
+        let auth = route.auth.as_ref().or(collection.auth.as_ref());
+        let (scope, limit) = match route.rate_limit {
+            Some(ref limit) => (Some(route.id.as_str()), limit),
+            None => (None, collection.rate_limit.as_ref()?),
+        };
+        let key = rate_limit::client_key(limit, auth, &headers, &query, remote_addr);
+        Some(state.rate_limiter.acquire(scope, key, limit))
     });
 
-    match matching_route {
-        Some(route) => {
-            // Apply delay if specified
-            if let Some(delay_ms) = route.delay_ms {
+    // Answer the request, then record it in the journal along with the route that matched
+    let response = async {
+        // A collection acting as an OpenID Connect provider serves its endpoints ahead of any route
+        if let Some(response) = oidc::handle(&collection, &state.oidc_sessions, &method, &path, &headers, &query, &body) {
+            return response;
+        }
+
+        if collection.kind == CollectionKind::Grpc {
+            if let Some(delay_ms) = matching_route.as_ref().and_then(|route| route.delay_ms) {
                 sleep(Duration::from_millis(delay_ms as u64)).await;
             }
+            return grpc::respond(&collection, &state.descriptors, matching_route.as_ref(), &path).await;
+        }
 
-            // Build response
-            let mut response = Response::builder()
-                .status(route.status_code);
-
-            // Check if Content-Type is already specified and add custom headers
-            let mut has_content_type = false;
-            if let Some(ref headers_json) = route.response_headers {
-                if let Ok(headers_map) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(headers_json.clone()) {
-                    for (key, value) in &headers_map {
-                        if key.to_lowercase() == "content-type" {
-                            has_content_type = true;
+        match matching_route {
+            Some(route) => {
+                if let Some(ref decision) = rate_limit {
+                    if !decision.allowed {
+                        return rate_limit::too_many_requests(decision);
+                    }
+                }
+
+                // Apply delay if specified
+                if let Some(delay_ms) = route.delay_ms {
+                    sleep(Duration::from_millis(delay_ms as u64)).await;
+                }
+
+                if let Some(auth) = route.auth.as_ref().or(collection.auth.as_ref()) {
+                    if let Some(rejection) = auth::check(auth, &headers, &query) {
+                        return rejection;
+                    }
+                }
+
+                if let Some(ref rules) = route.validation {
+                    let body_validator = match rules.body_schema {
+                        Some(ref schema) => match state.validators.get(&route, || validation::compile_schema(schema)) {
+                            Ok(validator) => Some(validator),
+                            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+                        },
+                        None => None,
+                    };
+                    let errors = validation::validate(rules, body_validator.as_deref(), &request, &body);
+                    if !errors.is_empty() {
+                        return validation::error_response(rules, errors);
+                    }
+                }
+
+                // Update the session first, so the response's templates see what this route stored
+                let session_id = request["cookies"][&collection.session.cookie_name].as_str();
+                let mut context = serde_json::json!({ "request": request, "session": state.sessions.get(session_id) });
+                let mut set_cookies = Vec::new();
+                if let Some(ref update) = route.session {
+                    let (session, cookie) = state.sessions.apply(&collection.session, session_id, update, &context);
+                    context["session"] = serde_json::Value::Object(session);
+                    set_cookies.extend(cookie);
+                }
+                set_cookies.extend(route.cookies.iter().filter_map(|cookie| cookies::set_cookie(cookie, &context)));
+                let set_cookies = set_cookies.into_iter().map(|cookie| (header::SET_COOKIE, cookie));
+
+                if route.kind == RouteKind::WebSocket {
+                    let Some(upgrade) = upgrade else {
+                        return (StatusCode::UPGRADE_REQUIRED, "Expected a WebSocket upgrade request").into_response();
+                    };
+                    let config = route.websocket.clone().unwrap_or_default();
+                    let mut response = websocket::respond(upgrade, config, route.path, request.clone(), state.websocket_tx.clone());
+                    response.headers_mut().extend(set_cookies);
+                    return response;
+                }
+
+                if route.kind == RouteKind::GraphQl {
+                    let graphql_request = if method == Method::GET {
+                        let query = &request["query"];
+                        graphql::GraphQlRequest {
+                            query: query["query"].as_str().unwrap_or_default().to_string(),
+                            operation_name: query["operationName"].as_str().map(str::to_string),
+                            variables: query["variables"].as_str().and_then(|v| serde_json::from_str(v).ok()),
                         }
-                        if let Ok(header_value) = value.as_str().unwrap_or("").parse::<axum::http::HeaderValue>() {
-                            response = response.header(key, header_value);
+                    } else {
+                        match serde_json::from_slice::<graphql::GraphQlRequest>(&body) {
+                            Ok(request) => request,
+                            Err(e) => {
+                                return (StatusCode::BAD_REQUEST, format!("Invalid GraphQL request: {}", e)).into_response();
+                            }
                         }
+                    };
+                    let config = route.graphql.clone().unwrap_or_default();
+                    let result = match state.graphql_schemas.get(&route, || graphql::Schema::parse(&config.schema)) {
+                        Ok(schema) => graphql::execute(&schema, &config, &graphql_request),
+                        Err(e) => graphql::error_response(e),
+                    };
+
+                    let mut response = axum::Json(result).into_response();
+                    *response.status_mut() = StatusCode::from_u16(route.status_code).unwrap_or(StatusCode::OK);
+                    response.headers_mut().extend(custom_headers(&route));
+                    response.headers_mut().extend(set_cookies);
+                    return response;
+                }
+
+                if route.kind == RouteKind::Sse {
+                    let mut response = sse::respond(route.sse.clone().unwrap_or_default(), request.clone(), state.websocket_tx.subscribe()).into_response();
+                    *response.status_mut() = StatusCode::from_u16(route.status_code).unwrap_or(StatusCode::OK);
+                    response.headers_mut().extend(custom_headers(&route));
+                    response.headers_mut().extend(set_cookies);
+                    return response;
+                }
+
+                // A route with several representations answers with the one the client prefers
+                let representation = if route.representations.is_empty() {
+                    None
+                } else {
+                    let accept: Vec<&str> = headers.get_all(header::ACCEPT).iter().filter_map(|v| v.to_str().ok()).collect();
+                    match content_type::negotiate(Some(&accept.join(",")), &route.representations) {
+                        Some(representation) => Some(representation),
+                        None => return not_acceptable(&route.representations),
+                    }
+                };
+
+                let (mut body, file_name) = match representation {
+                    Some(representation) => (Bytes::from(representation.body.clone()), None),
+                    None => match load_body(&route, &collection).await {
+                        Ok(loaded) => loaded,
+                        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+                    },
+                };
+
+                if route.template {
+                    if let Ok(text) = std::str::from_utf8(&body) {
+                        body = Bytes::from(template::render_with(text, &context, &mut Faker::new(route.fake_seed)));
                     }
                 }
+
+                let response_headers = match representation {
+                    Some(representation) => representation_headers(&route, representation, &body),
+                    None => response_headers(&route, &body, file_name.as_deref()),
+                };
+
+                if collection.contract_check {
+                    log_contract_violations(&state.specs, &collection, &route, &response_headers, &body);
+                }
+
+                // Add body, trickling it out if a bandwidth limit applies
+                let body = match route.bandwidth_bytes_per_sec.or(collection.bandwidth_bytes_per_sec) {
+                    Some(bytes_per_sec) if bytes_per_sec > 0 => throttled_body(body, bytes_per_sec),
+                    _ => Body::from(body),
+                };
+
+                let mut response = Response::new(body);
+                *response.status_mut() = StatusCode::from_u16(route.status_code).unwrap_or(StatusCode::OK);
+                *response.headers_mut() = response_headers;
+                response.headers_mut().extend(set_cookies);
+                response
             }
-            
-            // Add default Content-Type if not specified
-            if !has_content_type {
-                response = response.header("content-type", "application/json");
+            None => {
+                // Fall back to any static directory mounted over this path
+                match static_files::serve(&collection, &method, &uri, &headers).await {
+                    Some(response) => response,
+                    None => fallback::respond(&collection, &routes, &state.route_patterns, &method, &uri, &path),
+                }
             }
-
-            // Add body
-            let body = route.response_body.unwrap_or_else(|| "".to_string());
-            response.body(body).unwrap().into_response()
         }
-        None => {
-            (StatusCode::NOT_FOUND, "Route not found").into_response()
+    }
+    .await;
+
+    let mut response = response;
+    if let Some(ref decision) = rate_limit {
+        rate_limit::add_headers(&mut response, decision);
+    }
+
+    state.journal.record(JournalEntry::new(&method, &uri, &headers, &body, route_id, response.status()));
+    response
+}
+
+/// The route's configured response headers, skipping any that aren't valid HTTP headers.
+fn custom_headers(route: &Route) -> Vec<(HeaderName, HeaderValue)> {
+    header_pairs(&route.response_headers)
+}
+
+/// Headers are checked when they're saved, but ones stored before that are skipped
+/// rather than failing the response.
+fn header_pairs(headers: &ResponseHeaders) -> Vec<(HeaderName, HeaderValue)> {
+    headers.iter().filter_map(|header| header_pair(header).ok()).collect()
+}
+
+fn header_pair(header: &ResponseHeader) -> Result<(HeaderName, HeaderValue), String> {
+    let name = HeaderName::from_bytes(header.name.as_bytes())
+        .map_err(|_| format!("Invalid response header name: {:?}", header.name))?;
+    let value = HeaderValue::from_str(&header.value)
+        .map_err(|_| format!("Invalid value for response header {}: {:?}", header.name, header.value))?;
+    Ok((name, value))
+}
+
+/// Rejects configured headers that can't be sent, naming the first bad one.
+pub fn check_headers(headers: &ResponseHeaders) -> Result<(), String> {
+    headers.iter().try_for_each(|header| header_pair(header).map(|_| ()))
+}
+
+/// The headers an HTTP route is served with: its configured headers, plus a
+/// Content-Type detected from the body when none is configured.
+pub fn response_headers(route: &Route, body: &Bytes, file_name: Option<&str>) -> HeaderMap {
+    build_headers(&route.response_headers, body, file_name)
+}
+
+/// The headers a route is served with when answering with one of its representations:
+/// its configured headers, with the representation's Content-Type and `Vary: Accept`.
+pub fn representation_headers(route: &Route, representation: &Representation, body: &Bytes) -> HeaderMap {
+    let mut headers = build_headers(&route.response_headers, body, None);
+    if let Ok(media_type) = HeaderValue::from_str(&representation.media_type) {
+        headers.insert(header::CONTENT_TYPE, media_type);
+    }
+    headers.append(header::VARY, HeaderValue::from_static("accept"));
+    headers
+}
+
+/// The 406 sent when a request's `Accept` header rules out every representation.
+fn not_acceptable(representations: &[Representation]) -> Response {
+    let available: Vec<&str> = representations.iter().map(|r| r.media_type.as_str()).collect();
+    let body = serde_json::json!({
+        "error": "Not Acceptable",
+        "message": "None of the available representations is acceptable",
+        "available": available,
+    });
+    (StatusCode::NOT_ACCEPTABLE, [(header::VARY, "accept")], axum::Json(body)).into_response()
+}
+
+/// Turns configured headers into a `HeaderMap`, keeping repeated names, and adds a
+/// Content-Type detected from the body when none is configured.
+pub fn build_headers(configured: &ResponseHeaders, body: &Bytes, file_name: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (key, value) in header_pairs(configured) {
+        headers.append(key, value);
+    }
+
+    if !headers.contains_key(header::CONTENT_TYPE) {
+        if let Some(mime) = content_type::detect(body, file_name).and_then(|mime| HeaderValue::from_str(&mime).ok()) {
+            headers.insert(header::CONTENT_TYPE, mime);
         }
     }
+
+    headers
+}
+
+fn log_contract_violations(specs: &SpecCache, collection: &Collection, route: &Route, headers: &HeaderMap, body: &Bytes) {
+    let Some(ref text) = collection.openapi_spec else {
+        return;
+    };
+
+    let violations = match specs.get(text) {
+        Ok(spec) => contract::check_response(&spec, route.method.as_str(), &route.path, route.status_code, headers, body),
+        Err(e) => vec![e],
+    };
+    for violation in violations {
+        warn!("{} {} does not conform to the OpenAPI spec: {}", route.method.as_str(), route.path, violation);
+    }
 }
 
-fn method_matches(axum_method: &Method, route_method: &HttpMethod) -> bool {
+pub fn method_matches(axum_method: &Method, route_method: &HttpMethod) -> bool {
     match route_method {
-        HttpMethod::Get => axum_method == Method::GET,
-        HttpMethod::Post => axum_method == Method::POST,
-        HttpMethod::Put => axum_method == Method::PUT,
-        HttpMethod::Delete => axum_method == Method::DELETE,
-        HttpMethod::Patch => axum_method == Method::PATCH,
-        HttpMethod::Head => axum_method == Method::HEAD,
-        HttpMethod::Options => axum_method == Method::OPTIONS,
-    }
-}
\ No newline at end of file
+        HttpMethod::Any => true,
+        method => axum_method.as_str() == method.as_str(),
+    }
+}
+
+/// Resolves the route's body source to bytes, along with the file name when the
+/// body came from disk so the caller can guess its type from the extension.
+pub async fn load_body(route: &Route, collection: &Collection) -> Result<(Bytes, Option<String>), String> {
+    match &route.body_source {
+        BodySource::Inline => Ok((Bytes::from(route.response_body.clone().unwrap_or_default()), None)),
+        BodySource::Blob { data } => Ok((Bytes::from(data.clone()), None)),
+        BodySource::File { path } => {
+            let full_path = resolve_asset_path(collection, path)?;
+            let data = tokio::fs::read(&full_path)
+                .await
+                .map_err(|e| format!("Failed to read body file {}: {}", full_path.display(), e))?;
+            Ok((Bytes::from(data), Some(path.clone())))
+        }
+    }
+}
+
+/// Resolves a body file path against the collection's asset directory, refusing
+/// absolute paths and any that lead out of the directory, whether through `..` or a
+/// symlink. The file has to exist.
+pub fn resolve_asset_path(collection: &Collection, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Body file path must be relative to the asset directory: {}", path));
+    }
+
+    let asset_dir = collection
+        .asset_dir
+        .as_ref()
+        .ok_or("Collection has no asset directory for body files")?;
+    let asset_dir = std::fs::canonicalize(asset_dir)
+        .map_err(|e| format!("Failed to open asset directory {}: {}", asset_dir, e))?;
+    let full_path = asset_dir
+        .join(relative)
+        .canonicalize()
+        .map_err(|e| format!("Failed to open body file {}: {}", path, e))?;
+
+    if !full_path.starts_with(&asset_dir) {
+        return Err(format!("Body file path escapes the asset directory: {}", path));
+    }
+    Ok(full_path)
+}
+
+/// How often a throttled body emits a chunk.
+const THROTTLE_TICK_MS: u64 = 100;
+
+/// Streams `body` in chunks so that roughly `bytes_per_sec` bytes are sent per second.
+/// The response goes out with chunked transfer encoding since no length is known up front.
+fn throttled_body(body: Bytes, bytes_per_sec: u32) -> Body {
+    let chunk_size = ((bytes_per_sec as u64 * THROTTLE_TICK_MS / 1000) as usize).max(1);
+    let chunk_delay = Duration::from_micros(chunk_size as u64 * 1_000_000 / bytes_per_sec as u64);
+
+    let chunks = stream::unfold(body, move |mut remaining| async move {
+        if remaining.is_empty() {
+            return None;
+        }
+        let chunk = remaining.split_to(chunk_size.min(remaining.len()));
+        sleep(chunk_delay).await;
+        Some((Ok::<_, std::io::Error>(chunk), remaining))
+    });
+
+    Body::from_stream(chunks)
+}
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +8,30 @@ pub struct Collection {
     pub description: Option<String>,
     pub port: u16,
     pub base_path: Option<String>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
+    pub asset_dir: Option<String>,
+    pub static_mounts: Vec<StaticMount>,
+    pub kind: CollectionKind,
+    pub proto_files: Vec<String>,
+    /// OpenAPI document (JSON or YAML) the collection's routes are expected to follow.
+    pub openapi_spec: Option<String>,
+    /// Check every served response against `openapi_spec` and log the ones that don't conform.
+    pub contract_check: bool,
+    /// Serve the admin API under `/__mocify/` on this collection's mock server. Off by
+    /// default, as it can change routes and so read files the app can read. Read when
+    /// the server starts; `update_collection` restarts a running server when it changes.
+    pub admin_api: bool,
+    /// How requests no route matches are answered; a plain-text 404 when unset.
+    pub fallback: Option<FallbackConfig>,
+    pub path_normalization: PathNormalization,
+    /// Credentials every route requires unless it sets its own `auth`.
+    pub auth: Option<AuthConfig>,
+    pub oidc: Option<OidcConfig>,
+    pub cors: CorsConfig,
+    pub rate_limit: Option<RateLimit>,
+    pub session: SessionConfig,
+    /// Compress responses the client accepts compressed; off when unset.
+    pub compression: Option<CompressionConfig>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -17,18 +41,50 @@ pub struct Route {
     pub id: String,
     pub collection_id: String,
     pub name: String,
+    pub kind: RouteKind,
     pub method: HttpMethod,
     pub path: String,
+    pub path_match: PathMatch,
     pub status_code: u16,
     pub response_body: Option<String>,
-    pub response_headers: Option<serde_json::Value>,
+    pub body_source: BodySource,
+    /// Bodies to choose between by the request's `Accept` header, in place of the
+    /// route's own body. The first is sent when the client accepts anything.
+    pub representations: Vec<Representation>,
+    pub sse: Option<SseConfig>,
+    pub websocket: Option<WebSocketConfig>,
+    pub graphql: Option<GraphQlConfig>,
+    pub grpc: Option<GrpcResponse>,
+    pub validation: Option<RequestValidation>,
+    /// Render the response body as a template (`{{ request.* }}`, `{{ session.* }}`, `{{ fake.* }}`, `{{#repeat}}`).
+    pub template: bool,
+    /// Seed for the body's `fake.*` helpers, so every response carries the same generated data.
+    pub fake_seed: Option<u64>,
+    /// Conditions on the query, headers and body a request must meet, beyond method and path.
+    pub request_match: Option<RequestMatch>,
+    /// Routes are tried from highest to lowest priority; the first that matches answers.
+    pub priority: i32,
+    /// Credentials this route requires, replacing its collection's `auth`.
+    pub auth: Option<AuthConfig>,
+    /// Replaces the collection's `rate_limit`, with a budget of the route's own.
+    pub rate_limit: Option<RateLimit>,
+    /// Cookies set on every response, in order.
+    pub cookies: Vec<ResponseCookie>,
+    /// Values this route stores in, or removes from, the caller's session.
+    pub session: Option<SessionUpdate>,
+    pub response_headers: ResponseHeaders,
     pub delay_ms: Option<u32>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+/// A route's HTTP method, stored and sent to the frontend as its name. Besides the
+/// standard verbs, `ANY` matches every method and other tokens (`PROPFIND`, `REPORT`,
+/// `PURGE`, ...) are kept as custom methods. Names are case-insensitive and stored in
+/// upper case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum HttpMethod {
     Get,
     Post,
@@ -37,10 +93,666 @@ pub enum HttpMethod {
     Patch,
     Head,
     Options,
+    Trace,
+    Any,
+    Custom(String),
 }
 
-impl HttpMethod {
+/// The protocol a collection's mock server speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionKind {
+    #[default]
+    Http,
+    /// gRPC over HTTP/2; routes are RPC paths (`/package.Service/Method`) answered with
+    /// `Route::grpc`, typed by the collection's `proto_files`.
+    Grpc,
+}
+
+impl CollectionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollectionKind::Http => "http",
+            CollectionKind::Grpc => "grpc",
+        }
+    }
+}
+
+/// How `Route::path` is compared with the request path. Template and regex routes
+/// expose what they capture to templates as `request.params`. Variants are ordered
+/// from most to least specific, which is how routes of equal priority are tried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathMatch {
+    #[default]
+    Exact,
+    /// `{name}` or `:name` segments match any one segment, and a final `{name*}`
+    /// matches the rest of the path.
+    Template,
+    /// A regular expression matched against the whole path; named and numbered
+    /// groups become params.
+    Regex,
+}
+
+impl PathMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathMatch::Exact => "exact",
+            PathMatch::Template => "template",
+            PathMatch::Regex => "regex",
+        }
+    }
+}
+
+/// How a route responds once matched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteKind {
+    /// A single request/response exchange.
+    #[default]
+    Http,
+    /// A scripted Server-Sent Events stream described by `Route::sse`.
+    Sse,
+    /// A WebSocket endpoint whose behavior is described by `Route::websocket`.
+    WebSocket,
+    /// A GraphQL endpoint driven by the schema and operations in `Route::graphql`.
+    #[serde(rename = "graphql")]
+    GraphQl,
+}
+
+impl RouteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteKind::Http => "http",
+            RouteKind::Sse => "sse",
+            RouteKind::WebSocket => "websocket",
+            RouteKind::GraphQl => "graphql",
+        }
+    }
+}
+
+/// The event script for a Server-Sent Events route.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SseConfig {
+    pub events: Vec<SseEvent>,
+    /// Start over from the first event once the script runs out, instead of closing the stream.
+    #[serde(default)]
+    pub repeat: bool,
+    /// Interval for `: keep-alive` comment lines while the stream is idle.
+    pub keep_alive_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SseEvent {
+    /// Event name sent as the `event:` field; omitted for plain `message` events.
+    pub event: Option<String>,
+    /// Event payload; may contain `{{ }}` template placeholders.
+    pub data: String,
+    pub id: Option<String>,
+    pub retry_ms: Option<u64>,
+    /// Wait before sending this event.
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+/// Scripted behavior for a WebSocket route. Frame payloads may contain `{{ }}` template
+/// placeholders; `request` is always in scope, plus `message` for replies and `tick` for pushes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebSocketConfig {
+    /// Frames sent to each client as soon as it connects.
+    #[serde(default)]
+    pub on_connect: Vec<String>,
+    /// Replies to incoming text frames; the first matching rule wins.
+    #[serde(default)]
+    pub rules: Vec<WebSocketRule>,
+    /// Send unmatched incoming frames straight back to the client.
+    #[serde(default)]
+    pub echo: bool,
+    /// Frames pushed to each client on a fixed interval.
+    #[serde(default)]
+    pub periodic: Vec<WebSocketPush>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketRule {
+    pub matcher: MessageMatcher,
+    pub replies: Vec<String>,
+}
+
+/// How an incoming WebSocket text frame is compared against a rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum MessageMatcher {
+    Exact(String),
+    Contains(String),
+    Regex(String),
+    /// The frame parses as JSON and contains every field of this value.
+    JsonPartial(serde_json::Value),
+    Any,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketPush {
+    pub interval_ms: u64,
+    pub message: String,
+}
+
+/// Schema and canned results for a GraphQL route.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphQlConfig {
+    /// The schema in SDL form.
+    pub schema: String,
+    /// Canned results, checked in order; fields they leave out are generated from the schema.
+    #[serde(default)]
+    pub operations: Vec<GraphQlOperation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphQlOperation {
+    /// Operation name to match; any operation when omitted.
+    pub operation_name: Option<String>,
+    /// Variables the request must contain (extra variables are ignored).
+    pub variables: Option<serde_json::Value>,
+    /// Result data, shaped like the `data` member of a GraphQL response.
+    pub data: Option<serde_json::Value>,
+    /// Errors to include in the response, as GraphQL error objects.
+    pub errors: Option<serde_json::Value>,
+}
+
+/// Canned reply for an RPC in a gRPC collection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrpcResponse {
+    /// Response messages as JSON, encoded to the RPC's output type. Unary RPCs use the
+    /// first; server-streaming RPCs send them all.
+    #[serde(default)]
+    pub messages: Vec<serde_json::Value>,
+    /// gRPC status code sent in the trailers, `0` (OK) by default.
+    #[serde(default)]
+    pub status: u32,
+    pub status_message: Option<String>,
+    /// Pause between streamed messages.
+    #[serde(default)]
+    pub stream_interval_ms: u64,
+}
+
+/// Credentials a request must present. Set on a collection it applies to every route;
+/// a route's own requirement replaces the collection's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthConfig {
+    /// No credentials needed, which lets a route opt out of its collection's requirement.
+    None,
+    /// One of `keys` in the `name` header or query parameter.
+    ApiKey {
+        #[serde(default)]
+        location: ApiKeyLocation,
+        name: String,
+        keys: Vec<String>,
+    },
+    /// HTTP Basic credentials matching one of `users`.
+    Basic {
+        users: Vec<BasicUser>,
+        realm: Option<String>,
+    },
+    /// A Bearer token equal to one of `tokens`.
+    Bearer { tokens: Vec<String> },
+    /// A Bearer token that is a JWT signed with `key` and carrying the expected claims.
+    Jwt(JwtAuth),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasicUser {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtAuth {
+    pub algorithm: jsonwebtoken::Algorithm,
+    /// The shared secret for `HS*` algorithms, otherwise a PEM-encoded public key.
+    pub key: String,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    /// Claims the token must carry. A claim also matches when it's an array containing
+    /// the value, or, for strings, a space-separated list (like `scope`) containing
+    /// every word of it. Tokens with the right signature but wrong claims get a 403.
+    #[serde(default)]
+    pub required_claims: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Limits how often each client may call. Set on a collection it applies to every route,
+/// which then share one budget; a route's own limit replaces the collection's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimit {
+    #[serde(default)]
+    pub algorithm: RateLimitAlgorithm,
+    /// Requests allowed per window, or the capacity of a token bucket. `0` turns every
+    /// request away.
+    pub limit: u32,
+    /// The window length, or how long an empty token bucket takes to refill.
+    pub window_secs: u64,
+    #[serde(default)]
+    pub key: RateLimitKey,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAlgorithm {
+    /// Counts requests in consecutive windows, resetting at the end of each.
+    #[default]
+    FixedWindow,
+    /// Refills continuously, allowing bursts up to `limit`.
+    TokenBucket,
+}
+
+/// What tells clients apart for rate limiting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RateLimitKey {
+    #[default]
+    Ip,
+    Header { name: String },
+    /// The API key of the route's `api_key` auth, or the `Authorization` header otherwise.
+    ApiKey,
+}
+
+/// Turns a collection's mock server into an OAuth2 / OpenID Connect provider that
+/// signs RS256 tokens for the configured users and clients. The provider's endpoints
+/// answer ahead of the collection's routes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcConfig {
+    /// `http://localhost:{port}` when unset.
+    pub issuer: Option<String>,
+    /// PEM-encoded RSA private key the tokens are signed with.
+    pub signing_key: String,
+    #[serde(default)]
+    pub clients: Vec<OidcClient>,
+    #[serde(default)]
+    pub users: Vec<OidcUser>,
+    /// Lifetime of access and ID tokens.
+    #[serde(default = "default_token_ttl")]
+    pub token_ttl_secs: u64,
+}
+
+fn default_token_ttl() -> u64 {
+    3600
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcClient {
+    pub client_id: String,
+    /// Public clients have no secret and must use PKCE.
+    pub client_secret: Option<String>,
+    /// Allowed redirect URIs; any is accepted when empty.
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcUser {
+    /// The `sub` claim.
+    pub subject: String,
+    pub username: String,
+    pub password: String,
+    /// Extra claims for ID tokens and userinfo, such as `email` or `name`.
+    #[serde(default)]
+    pub claims: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Checks an incoming request must pass before its route responds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestValidation {
+    /// JSON Schema the request body must satisfy.
+    pub body_schema: Option<serde_json::Value>,
+    /// Header names that must be present (case-insensitive).
+    #[serde(default)]
+    pub required_headers: Vec<String>,
+    /// Query parameters that must be present.
+    #[serde(default)]
+    pub required_query_params: Vec<String>,
+    /// Status for requests that fail validation, `400` by default (`422` is the usual alternative).
+    pub status_code: Option<u16>,
+}
+
+/// Conditions a request must meet for a route to answer it. Every listed condition
+/// has to hold.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestMatch {
+    #[serde(default)]
+    pub query: Vec<FieldMatch>,
+    /// Header names are compared case-insensitively.
+    #[serde(default)]
+    pub headers: Vec<FieldMatch>,
+    /// Fields of an `application/x-www-form-urlencoded` body.
+    #[serde(default)]
+    pub form: Vec<FieldMatch>,
+    #[serde(default)]
+    pub cookies: Vec<FieldMatch>,
+    pub body: Option<BodyMatcher>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMatch {
+    pub name: String,
+    pub matcher: FieldMatcher,
+}
+
+/// How a query parameter, header or form field is compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum FieldMatcher {
+    Exact(String),
+    Regex(String),
+    Present,
+    Absent,
+}
+
+/// How a request body is compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum BodyMatcher {
+    Exact(String),
+    Contains(String),
+    Regex(String),
+    /// The body parses as JSON equal to this value.
+    JsonEquals(serde_json::Value),
+    /// The body parses as JSON and contains every field of this value.
+    JsonPartial(serde_json::Value),
+}
+
+/// One of the bodies a route can answer with, sent with its own `Content-Type`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Representation {
+    /// A concrete media type such as `application/xml` or `text/csv; charset=utf-8`.
+    pub media_type: String,
+    pub body: String,
+}
+
+/// A response's headers, sent in order. A name may appear more than once, as `Link`
+/// and `Set-Cookie` often do.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct ResponseHeaders(pub Vec<ResponseHeader>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseHeader {
+    pub name: String,
+    pub value: String,
+}
+
+impl std::ops::Deref for ResponseHeaders {
+    type Target = [ResponseHeader];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Reads a list of `{ "name", "value" }` pairs, or the `{ "name": "value" }` object
+/// headers were stored as before, taking non-string values as their JSON text.
+impl<'de> Deserialize<'de> for ResponseHeaders {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Headers {
+            Pairs(Vec<ResponseHeader>),
+            Object(serde_json::Map<String, serde_json::Value>),
+        }
+
+        let pairs = match Headers::deserialize(deserializer)? {
+            Headers::Pairs(pairs) => pairs,
+            Headers::Object(object) => object
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        serde_json::Value::String(value) => value,
+                        value => value.to_string(),
+                    };
+                    ResponseHeader { name, value }
+                })
+                .collect(),
+        };
+        Ok(Self(pairs))
+    }
+}
+
+/// A cookie a route sets with `Set-Cookie`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCookie {
+    pub name: String,
+    /// Rendered as a template, so it can carry `{{ uuid }}` or echo part of the request.
+    pub value: String,
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    /// `0` tells the browser to delete the cookie; a session cookie when unset.
+    pub max_age_secs: Option<i64>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+/// How a collection's mock server keeps track of sessions. Session data lives in
+/// memory and is lost when the server stops.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// The cookie holding the session ID.
+    #[serde(default = "default_session_cookie")]
+    pub cookie_name: String,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self { cookie_name: default_session_cookie() }
+    }
+}
+
+fn default_session_cookie() -> String {
+    "mocify_session".to_string()
+}
+
+/// Changes a route makes to the caller's session, which templates read as `session.*`.
+/// A session is started, and its cookie set, the first time a route stores a value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionUpdate {
+    /// Values to store. Strings are rendered as templates first, so
+    /// `{ "user": "{{ request.query.user }}" }` remembers who logged in.
+    #[serde(default)]
+    pub set: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    /// Ends the session and expires its cookie, as a logout would.
+    #[serde(default)]
+    pub clear: bool,
+}
+
+/// The CORS policy of a collection's mock server, read when the server starts. The
+/// default allows any origin, method and header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// `false` sends no CORS headers at all, as a backend without CORS support would.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Origins such as `http://localhost:3000`; any origin when empty.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Any method when empty.
+    #[serde(default)]
+    pub allowed_methods: Vec<String>,
+    /// Request headers allowed in preflighted requests; any when empty.
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    /// Response headers scripts may read; all when empty, unless credentials are allowed.
+    #[serde(default)]
+    pub exposed_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight response.
+    pub max_age_secs: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_origins: Vec::new(),
+            allowed_methods: Vec::new(),
+            allowed_headers: Vec::new(),
+            exposed_headers: Vec::new(),
+            allow_credentials: false,
+            max_age_secs: None,
+        }
+    }
+}
+
+/// How a collection's mock server compresses responses, read when the server starts.
+/// Images, gRPC and server-sent events are never compressed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// Encodings the server may choose from by the request's `Accept-Encoding`; any when empty.
+    #[serde(default)]
+    pub encodings: Vec<ContentEncoding>,
+    /// Compress with this encoding whether or not the client accepts it.
+    pub force: Option<ContentEncoding>,
+    /// Sent as the `Content-Encoding` of every response in place of the real one, to test
+    /// clients against a body that doesn't match its label. Empty removes the header, so
+    /// compressed bodies arrive unlabeled.
+    pub mislabel_as: Option<String>,
+    /// Bodies smaller than this are sent uncompressed; 32 bytes when unset.
+    pub min_size_bytes: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Br,
+    Zstd,
+}
+
+/// How request paths are compared with route paths. With every option off, matching
+/// is strict: paths must be equal byte for byte.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathNormalization {
+    /// `/users/` matches `/users`.
+    #[serde(default)]
+    pub ignore_trailing_slash: bool,
+    /// `/Users` matches `/users`.
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// `//users` matches `/users`.
+    #[serde(default)]
+    pub collapse_slashes: bool,
+    /// `/caf%C3%A9` matches `/café`.
+    #[serde(default)]
+    pub decode_percent: bool,
+}
+
+/// What a mock server answers when neither a route nor a static mount matches a request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FallbackConfig {
+    /// A fixed response.
+    Response {
+        status_code: u16,
+        body: Option<String>,
+        #[serde(default)]
+        headers: ResponseHeaders,
+    },
+    /// A 404 whose JSON body lists the routes the request nearly matched (right path but
+    /// wrong method, a trailing slash or letter case apart, ...) and why each missed.
+    ClosestMatch,
+    /// Redirects to `location`, followed by the request's path and query when `append_path`
+    /// is set. The default 307 keeps the method and body, so another server can pick up
+    /// whatever the mock doesn't cover.
+    Redirect {
+        location: String,
+        #[serde(default = "default_redirect_status")]
+        status_code: u16,
+        #[serde(default)]
+        append_path: bool,
+    },
+}
+
+fn default_redirect_status() -> u16 {
+    307
+}
+
+/// A local directory served under a URL prefix alongside a collection's routes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticMount {
+    /// URL prefix such as `/static`.
+    pub prefix: String,
+    /// Directory to serve, resolved relative to the collection's `asset_dir` when not absolute.
+    pub dir: String,
+    /// Serve the directory's `index.html` for paths that don't exist, as single-page apps expect.
+    #[serde(default)]
+    pub spa_fallback: bool,
+}
+
+/// Where a route's response body comes from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BodySource {
+    /// The text stored in `response_body`.
+    #[default]
+    Inline,
+    /// A file on disk, resolved relative to the collection's `asset_dir`.
+    File { path: String },
+    /// Binary content stored in the database, base64-encoded over the wire.
+    Blob {
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+    },
+}
+
+impl BodySource {
     pub fn as_str(&self) -> &'static str {
+        match self {
+            BodySource::Inline => "inline",
+            BodySource::File { .. } => "file",
+            BodySource::Blob { .. } => "blob",
+        }
+    }
+}
+
+mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
         match self {
             HttpMethod::Get => "GET",
             HttpMethod::Post => "POST",
@@ -49,16 +761,87 @@ impl HttpMethod {
             HttpMethod::Patch => "PATCH",
             HttpMethod::Head => "HEAD",
             HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Any => "ANY",
+            HttpMethod::Custom(method) => method,
         }
     }
 }
 
+impl std::str::FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let method = value.trim().to_ascii_uppercase();
+        Ok(match method.as_str() {
+            "GET" => HttpMethod::Get,
+            "POST" => HttpMethod::Post,
+            "PUT" => HttpMethod::Put,
+            "DELETE" => HttpMethod::Delete,
+            "PATCH" => HttpMethod::Patch,
+            "HEAD" => HttpMethod::Head,
+            "OPTIONS" => HttpMethod::Options,
+            "TRACE" => HttpMethod::Trace,
+            "ANY" => HttpMethod::Any,
+            // CONNECT opens a tunnel rather than asking for a response, so there's nothing to mock
+            "CONNECT" => return Err("CONNECT routes are not supported".to_string()),
+            "" => return Err("HTTP method must not be empty".to_string()),
+            _ if method.bytes().all(is_token_byte) => HttpMethod::Custom(method),
+            _ => return Err(format!("Invalid HTTP method: {}", value)),
+        })
+    }
+}
+
+impl TryFrom<String> for HttpMethod {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
+    }
+}
+
+impl From<HttpMethod> for String {
+    fn from(method: HttpMethod) -> String {
+        method.as_str().to_string()
+    }
+}
+
+/// Whether `byte` may appear in an RFC 9110 token, such as a method or cookie name.
+pub fn is_token_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCollectionRequest {
     pub name: String,
     pub description: Option<String>,
     pub port: u16,
     pub base_path: Option<String>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
+    pub asset_dir: Option<String>,
+    #[serde(default)]
+    pub static_mounts: Vec<StaticMount>,
+    #[serde(default)]
+    pub kind: CollectionKind,
+    /// `.proto` files for gRPC collections, relative to `asset_dir`.
+    #[serde(default)]
+    pub proto_files: Vec<String>,
+    pub openapi_spec: Option<String>,
+    #[serde(default)]
+    pub contract_check: bool,
+    #[serde(default)]
+    pub admin_api: bool,
+    pub fallback: Option<FallbackConfig>,
+    #[serde(default)]
+    pub path_normalization: PathNormalization,
+    pub auth: Option<AuthConfig>,
+    pub oidc: Option<OidcConfig>,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    pub rate_limit: Option<RateLimit>,
+    #[serde(default)]
+    pub session: SessionConfig,
+    pub compression: Option<CompressionConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -68,30 +851,90 @@ pub struct UpdateCollectionRequest {
     pub description: Option<String>,
     pub port: Option<u16>,
     pub base_path: Option<String>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
+    pub asset_dir: Option<String>,
+    pub static_mounts: Option<Vec<StaticMount>>,
+    pub kind: Option<CollectionKind>,
+    pub proto_files: Option<Vec<String>>,
+    pub openapi_spec: Option<String>,
+    pub contract_check: Option<bool>,
+    pub admin_api: Option<bool>,
+    pub fallback: Option<FallbackConfig>,
+    pub path_normalization: Option<PathNormalization>,
+    pub auth: Option<AuthConfig>,
+    pub oidc: Option<OidcConfig>,
+    pub cors: Option<CorsConfig>,
+    pub rate_limit: Option<RateLimit>,
+    pub session: Option<SessionConfig>,
+    pub compression: Option<CompressionConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRouteRequest {
     pub collection_id: String,
     pub name: String,
+    #[serde(default)]
+    pub kind: RouteKind,
     pub method: HttpMethod,
     pub path: String,
+    #[serde(default)]
+    pub path_match: PathMatch,
     pub status_code: u16,
     pub response_body: Option<String>,
-    pub response_headers: Option<serde_json::Value>,
+    #[serde(default)]
+    pub body_source: BodySource,
+    #[serde(default)]
+    pub representations: Vec<Representation>,
+    pub sse: Option<SseConfig>,
+    pub websocket: Option<WebSocketConfig>,
+    pub graphql: Option<GraphQlConfig>,
+    pub grpc: Option<GrpcResponse>,
+    pub validation: Option<RequestValidation>,
+    #[serde(default)]
+    pub template: bool,
+    pub fake_seed: Option<u64>,
+    pub request_match: Option<RequestMatch>,
+    #[serde(default)]
+    pub priority: i32,
+    pub auth: Option<AuthConfig>,
+    pub rate_limit: Option<RateLimit>,
+    #[serde(default)]
+    pub cookies: Vec<ResponseCookie>,
+    pub session: Option<SessionUpdate>,
+    #[serde(default)]
+    pub response_headers: ResponseHeaders,
     pub delay_ms: Option<u32>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdateRouteRequest {
     pub id: String,
     pub name: Option<String>,
+    pub kind: Option<RouteKind>,
     pub method: Option<HttpMethod>,
     pub path: Option<String>,
+    pub path_match: Option<PathMatch>,
     pub status_code: Option<u16>,
     pub response_body: Option<String>,
-    pub response_headers: Option<serde_json::Value>,
+    pub body_source: Option<BodySource>,
+    pub representations: Option<Vec<Representation>>,
+    pub sse: Option<SseConfig>,
+    pub websocket: Option<WebSocketConfig>,
+    pub graphql: Option<GraphQlConfig>,
+    pub grpc: Option<GrpcResponse>,
+    pub validation: Option<RequestValidation>,
+    pub template: Option<bool>,
+    pub fake_seed: Option<u64>,
+    pub request_match: Option<RequestMatch>,
+    pub priority: Option<i32>,
+    pub auth: Option<AuthConfig>,
+    pub rate_limit: Option<RateLimit>,
+    pub cookies: Option<Vec<ResponseCookie>>,
+    pub session: Option<SessionUpdate>,
+    pub response_headers: Option<ResponseHeaders>,
     pub delay_ms: Option<u32>,
+    pub bandwidth_bytes_per_sec: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -103,6 +946,34 @@ pub struct ServerStatus {
     pub base_url: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushWebSocketMessageRequest {
+    pub port: u16,
+    /// Only reach clients connected to this route path; all clients when omitted.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+/// How one route measures up against its collection's OpenAPI document.
+#[derive(Debug, Serialize)]
+pub struct ContractReport {
+    pub route_id: String,
+    pub route_name: String,
+    pub method: String,
+    pub path: String,
+    /// Empty when the route's response conforms.
+    pub violations: Vec<String>,
+}
+
+/// Fills a route's body with data generated from a JSON Schema.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateBodyRequest {
+    pub route_id: String,
+    pub schema: serde_json::Value,
+    /// Seed for reproducible output; random when omitted.
+    pub seed: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TestRouteRequest {
     pub route_id: String,
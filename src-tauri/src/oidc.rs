@@ -0,0 +1,502 @@
+use axum::{
+    body::Bytes,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey, LineEnding};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::traits::PublicKeyParts;
+use rsa::RsaPrivateKey;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::models::{Collection, OidcClient, OidcConfig, OidcUser};
+use crate::template;
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+const JWKS_PATH: &str = "/oauth2/jwks";
+const AUTHORIZE_PATH: &str = "/oauth2/authorize";
+const TOKEN_PATH: &str = "/oauth2/token";
+const USERINFO_PATH: &str = "/oauth2/userinfo";
+
+/// How long an authorization code can be exchanged for tokens.
+const CODE_TTL_SECS: i64 = 60;
+
+/// The authorization codes and refresh tokens a provider has handed out. They live in
+/// memory, so a restarted server forgets them.
+#[derive(Clone, Default)]
+pub struct OidcSessions {
+    codes: Arc<Mutex<HashMap<String, AuthorizationCode>>>,
+    refresh_tokens: Arc<Mutex<HashMap<String, Grant>>>,
+}
+
+/// What a client has been allowed: a user's tokens, or its own for client credentials.
+#[derive(Clone)]
+struct Grant {
+    client_id: String,
+    subject: Option<String>,
+    scope: String,
+    nonce: Option<String>,
+}
+
+struct AuthorizationCode {
+    grant: Grant,
+    redirect_uri: String,
+    code_challenge: Option<String>,
+    code_challenge_method: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// A provider config to start from: a fresh signing key, a confidential client, a
+/// public (PKCE) client and a demo user. Generating the key takes a moment.
+pub fn template() -> Result<OidcConfig, String> {
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).map_err(|e| e.to_string())?;
+    let signing_key = key.to_pkcs1_pem(LineEnding::LF).map_err(|e| e.to_string())?.to_string();
+
+    let claims = json!({
+        "name": "Alice Example",
+        "email": "alice@example.com",
+        "email_verified": true,
+    });
+
+    Ok(OidcConfig {
+        issuer: None,
+        signing_key,
+        clients: vec![
+            OidcClient {
+                client_id: "mock-client".to_string(),
+                client_secret: Some("mock-secret".to_string()),
+                redirect_uris: Vec::new(),
+            },
+            OidcClient {
+                client_id: "mock-spa".to_string(),
+                client_secret: None,
+                redirect_uris: Vec::new(),
+            },
+        ],
+        users: vec![OidcUser {
+            subject: "user-1".to_string(),
+            username: "alice".to_string(),
+            password: "password".to_string(),
+            claims: claims.as_object().cloned().unwrap_or_default(),
+        }],
+        token_ttl_secs: 3600,
+    })
+}
+
+/// Rejects a provider config whose signing key can't be read.
+pub fn check_config(config: &OidcConfig) -> Result<(), String> {
+    signing_key(config).map(|_| ())
+}
+
+/// Serves the provider's endpoints, or returns `None` when `path` isn't one of them:
+///
+/// - `GET /.well-known/openid-configuration`: discovery
+/// - `GET /oauth2/jwks`: the public signing key
+/// - `GET /oauth2/authorize`: a login form, skipped when `login_hint` names a user;
+///   `POST` the form to get redirected back with a code
+/// - `POST /oauth2/token`: `authorization_code` (with PKCE), `client_credentials` and
+///   `refresh_token` grants
+/// - `GET /oauth2/userinfo`: the claims of the user an access token was issued to
+pub fn handle(
+    collection: &Collection,
+    sessions: &OidcSessions,
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+    query: &Map<String, Value>,
+    body: &Bytes,
+) -> Option<Response> {
+    let config = collection.oidc.as_ref()?;
+    let provider = Provider {
+        config,
+        sessions,
+        issuer: config
+            .issuer
+            .clone()
+            .unwrap_or_else(|| format!("http://localhost:{}", collection.port))
+            .trim_end_matches('/')
+            .to_string(),
+    };
+
+    let response = match (method.as_str(), path) {
+        ("GET", DISCOVERY_PATH) => provider.discovery(),
+        ("GET", JWKS_PATH) => provider.jwks(),
+        ("GET", AUTHORIZE_PATH) => provider.authorize(query, None),
+        ("POST", AUTHORIZE_PATH) => {
+            let form = form(body);
+            let credentials = (
+                form.get("username").and_then(Value::as_str).unwrap_or_default(),
+                form.get("password").and_then(Value::as_str).unwrap_or_default(),
+            );
+            provider.authorize(&form, Some(credentials))
+        }
+        ("POST", TOKEN_PATH) => provider.token(headers, &form(body)),
+        ("GET" | "POST", USERINFO_PATH) => provider.userinfo(headers),
+        _ => return None,
+    };
+    Some(response)
+}
+
+struct Provider<'a> {
+    config: &'a OidcConfig,
+    sessions: &'a OidcSessions,
+    issuer: String,
+}
+
+impl Provider<'_> {
+    fn discovery(&self) -> Response {
+        Json(json!({
+            "issuer": self.issuer,
+            "authorization_endpoint": format!("{}{}", self.issuer, AUTHORIZE_PATH),
+            "token_endpoint": format!("{}{}", self.issuer, TOKEN_PATH),
+            "userinfo_endpoint": format!("{}{}", self.issuer, USERINFO_PATH),
+            "jwks_uri": format!("{}{}", self.issuer, JWKS_PATH),
+            "response_types_supported": ["code"],
+            "grant_types_supported": ["authorization_code", "client_credentials", "refresh_token"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["RS256"],
+            "scopes_supported": ["openid", "profile", "email", "offline_access"],
+            "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post", "none"],
+            "code_challenge_methods_supported": ["S256", "plain"],
+        }))
+        .into_response()
+    }
+
+    fn jwks(&self) -> Response {
+        match signing_key(self.config) {
+            Ok(key) => {
+                let (kid, n, e) = public_components(&key);
+                Json(json!({
+                    "keys": [{ "kty": "RSA", "use": "sig", "alg": "RS256", "kid": kid, "n": n, "e": e }]
+                }))
+                .into_response()
+            }
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        }
+    }
+
+    /// Handles an authorization request. Without `credentials` the user is taken from
+    /// `login_hint` or asked to log in; with them, they're checked first.
+    fn authorize(&self, params: &Map<String, Value>, credentials: Option<(&str, &str)>) -> Response {
+        let param = |name: &str| params.get(name).and_then(Value::as_str).unwrap_or_default();
+
+        // Until the client and redirect URI check out, errors can't be sent back to the client
+        let Some(client) = self.client(param("client_id")) else {
+            return (StatusCode::BAD_REQUEST, "Unknown client_id").into_response();
+        };
+        let redirect_uri = param("redirect_uri");
+        if redirect_uri.is_empty() || !(client.redirect_uris.is_empty() || client.redirect_uris.iter().any(|uri| uri == redirect_uri)) {
+            return (StatusCode::BAD_REQUEST, "redirect_uri is missing or not registered for this client").into_response();
+        }
+
+        let state = params.get("state").and_then(Value::as_str);
+        if param("response_type") != "code" {
+            return redirect(redirect_uri, &[("error", "unsupported_response_type")], state);
+        }
+        let code_challenge = params.get("code_challenge").and_then(Value::as_str).map(str::to_string);
+        if client.client_secret.is_none() && code_challenge.is_none() {
+            return redirect(redirect_uri, &[("error", "invalid_request"), ("error_description", "Public clients must use PKCE")], state);
+        }
+
+        let user = match credentials {
+            Some((username, password)) => match self.config.users.iter().find(|u| u.username == username && u.password == password) {
+                Some(user) => user,
+                None => return login_form(params, Some("Invalid username or password")),
+            },
+            None => match self.config.users.iter().find(|u| u.username == param("login_hint")) {
+                Some(user) => user,
+                None => return login_form(params, None),
+            },
+        };
+
+        let code = Uuid::new_v4().simple().to_string();
+        let mut codes = self.sessions.codes.lock().unwrap();
+        codes.retain(|_, code| code.expires_at > Utc::now());
+        codes.insert(
+            code.clone(),
+            AuthorizationCode {
+                grant: Grant {
+                    client_id: client.client_id.clone(),
+                    subject: Some(user.subject.clone()),
+                    scope: param("scope").to_string(),
+                    nonce: params.get("nonce").and_then(Value::as_str).map(str::to_string),
+                },
+                redirect_uri: redirect_uri.to_string(),
+                code_challenge,
+                code_challenge_method: params
+                    .get("code_challenge_method")
+                    .and_then(Value::as_str)
+                    .unwrap_or("plain")
+                    .to_string(),
+                expires_at: Utc::now() + Duration::seconds(CODE_TTL_SECS),
+            },
+        );
+        drop(codes);
+        redirect(redirect_uri, &[("code", &code)], state)
+    }
+
+    fn token(&self, headers: &HeaderMap, form: &Map<String, Value>) -> Response {
+        let param = |name: &str| form.get(name).and_then(Value::as_str).unwrap_or_default();
+
+        // Clients authenticate with Basic credentials or in the form
+        let basic = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+            .and_then(|decoded| String::from_utf8(decoded).ok());
+        let (client_id, client_secret) = match basic {
+            Some(ref credentials) => {
+                let (id, secret) = credentials.split_once(':').unwrap_or((credentials, ""));
+                (id.to_string(), Some(secret.to_string()))
+            }
+            None => (param("client_id").to_string(), form.get("client_secret").and_then(Value::as_str).map(str::to_string)),
+        };
+
+        let Some(client) = self.client(&client_id) else {
+            return token_error(StatusCode::UNAUTHORIZED, "invalid_client", "Unknown client");
+        };
+        if client.client_secret.is_some() && client.client_secret != client_secret {
+            return token_error(StatusCode::UNAUTHORIZED, "invalid_client", "Invalid client secret");
+        }
+
+        let grant = match param("grant_type") {
+            "authorization_code" => {
+                let Some(code) = self.sessions.codes.lock().unwrap().remove(param("code")) else {
+                    return token_error(StatusCode::BAD_REQUEST, "invalid_grant", "Unknown or already used code");
+                };
+                if code.expires_at < Utc::now() || code.grant.client_id != client.client_id {
+                    return token_error(StatusCode::BAD_REQUEST, "invalid_grant", "The code has expired or belongs to another client");
+                }
+                if param("redirect_uri") != code.redirect_uri {
+                    return token_error(StatusCode::BAD_REQUEST, "invalid_grant", "redirect_uri does not match the authorization request");
+                }
+                if let Some(ref challenge) = code.code_challenge {
+                    if !pkce_matches(challenge, &code.code_challenge_method, param("code_verifier")) {
+                        return token_error(StatusCode::BAD_REQUEST, "invalid_grant", "code_verifier does not match the code_challenge");
+                    }
+                }
+                code.grant
+            }
+            "client_credentials" => {
+                if client.client_secret.is_none() {
+                    return token_error(StatusCode::UNAUTHORIZED, "unauthorized_client", "Public clients can't use client credentials");
+                }
+                Grant {
+                    client_id: client.client_id.clone(),
+                    subject: None,
+                    scope: param("scope").to_string(),
+                    nonce: None,
+                }
+            }
+            "refresh_token" => {
+                // Refresh tokens are single-use; a new one comes back with the tokens
+                let grant = self.sessions.refresh_tokens.lock().unwrap().remove(param("refresh_token"));
+                match grant {
+                    Some(grant) if grant.client_id == client.client_id => grant,
+                    _ => return token_error(StatusCode::BAD_REQUEST, "invalid_grant", "Unknown or already used refresh token"),
+                }
+            }
+            _ => return token_error(StatusCode::BAD_REQUEST, "unsupported_grant_type", "Unsupported grant_type"),
+        };
+
+        match self.issue_tokens(&grant) {
+            Ok(tokens) => {
+                let mut response = Json(tokens).into_response();
+                response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+                response
+            }
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        }
+    }
+
+    fn userinfo(&self, headers: &HeaderMap) -> Response {
+        let unauthorized = |description: &str| {
+            let challenge = format!("Bearer error=\"invalid_token\", error_description=\"{}\"", description);
+            let mut response = StatusCode::UNAUTHORIZED.into_response();
+            if let Ok(value) = HeaderValue::from_str(&challenge) {
+                response.headers_mut().insert(header::WWW_AUTHENTICATE, value);
+            }
+            response
+        };
+
+        let Some(token) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return unauthorized("Missing access token");
+        };
+        let key = match signing_key(self.config) {
+            Ok(key) => key,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        };
+        let (_, n, e) = public_components(&key);
+        let Ok(decoding_key) = DecodingKey::from_rsa_components(&n, &e) else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[&self.issuer]);
+        validation.validate_aud = false;
+        let Ok(data) = jsonwebtoken::decode::<Map<String, Value>>(token.trim(), &decoding_key, &validation) else {
+            return unauthorized("The access token is invalid or has expired");
+        };
+
+        let subject = data.claims.get("sub").and_then(Value::as_str).unwrap_or_default();
+        match self.config.users.iter().find(|user| user.subject == subject) {
+            Some(user) => {
+                let mut claims = user.claims.clone();
+                claims.insert("sub".to_string(), Value::String(user.subject.clone()));
+                claims.entry("preferred_username").or_insert_with(|| Value::String(user.username.clone()));
+                Json(claims).into_response()
+            }
+            None => unauthorized("The access token was not issued to a user"),
+        }
+    }
+
+    fn issue_tokens(&self, grant: &Grant) -> Result<Value, String> {
+        let key = signing_key(self.config)?;
+        let (kid, _, _) = public_components(&key);
+        let der = key.to_pkcs1_der().map_err(|e| e.to_string())?;
+        let encoding_key = EncodingKey::from_rsa_der(der.as_bytes());
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(kid);
+
+        let now = Utc::now().timestamp();
+        let expires_at = now + self.config.token_ttl_secs as i64;
+        let subject = grant.subject.clone().unwrap_or_else(|| grant.client_id.clone());
+        let sign = |claims: &Value| jsonwebtoken::encode(&header, claims, &encoding_key).map_err(|e| e.to_string());
+
+        let access_token = sign(&json!({
+            "iss": self.issuer,
+            "sub": subject,
+            "aud": grant.client_id,
+            "client_id": grant.client_id,
+            "scope": grant.scope,
+            "iat": now,
+            "exp": expires_at,
+            "jti": Uuid::new_v4().to_string(),
+        }))?;
+
+        let mut tokens = json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": self.config.token_ttl_secs,
+            "scope": grant.scope,
+        });
+
+        let Some(user) = grant.subject.as_ref().and_then(|subject| self.config.users.iter().find(|u| &u.subject == subject)) else {
+            return Ok(tokens);
+        };
+
+        if grant.scope.split_whitespace().any(|scope| scope == "openid") {
+            let mut claims = user.claims.clone();
+            claims.entry("preferred_username").or_insert_with(|| Value::String(user.username.clone()));
+            claims.insert("iss".to_string(), Value::String(self.issuer.clone()));
+            claims.insert("sub".to_string(), Value::String(user.subject.clone()));
+            claims.insert("aud".to_string(), Value::String(grant.client_id.clone()));
+            claims.insert("iat".to_string(), json!(now));
+            claims.insert("exp".to_string(), json!(expires_at));
+            if let Some(ref nonce) = grant.nonce {
+                claims.insert("nonce".to_string(), Value::String(nonce.clone()));
+            }
+            tokens["id_token"] = Value::String(sign(&Value::Object(claims))?);
+        }
+
+        let refresh_token = Uuid::new_v4().simple().to_string();
+        self.sessions.refresh_tokens.lock().unwrap().insert(refresh_token.clone(), grant.clone());
+        tokens["refresh_token"] = Value::String(refresh_token);
+        Ok(tokens)
+    }
+
+    fn client(&self, client_id: &str) -> Option<&OidcClient> {
+        self.config.clients.iter().find(|client| client.client_id == client_id)
+    }
+}
+
+fn signing_key(config: &OidcConfig) -> Result<RsaPrivateKey, String> {
+    RsaPrivateKey::from_pkcs1_pem(&config.signing_key)
+        .or_else(|_| RsaPrivateKey::from_pkcs8_pem(&config.signing_key))
+        .map_err(|e| format!("Invalid OIDC signing key: {}", e))
+}
+
+/// The key ID and base64url-encoded modulus and exponent of the public key.
+fn public_components(key: &RsaPrivateKey) -> (String, String, String) {
+    let n = key.n().to_bytes_be();
+    let kid = URL_SAFE_NO_PAD.encode(&Sha256::digest(&n)[..12]);
+    (kid, URL_SAFE_NO_PAD.encode(&n), URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()))
+}
+
+fn pkce_matches(challenge: &str, method: &str, verifier: &str) -> bool {
+    match method {
+        "S256" => URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) == challenge,
+        _ => verifier == challenge,
+    }
+}
+
+fn form(body: &Bytes) -> Map<String, Value> {
+    template::parse_urlencoded(&String::from_utf8_lossy(body))
+}
+
+/// Sends the browser back to the client with `params` (and `state`) in the query.
+fn redirect(redirect_uri: &str, params: &[(&str, &str)], state: Option<&str>) -> Response {
+    let query: Vec<String> = params
+        .iter()
+        .copied()
+        .chain(state.map(|state| ("state", state)))
+        .map(|(name, value)| format!("{}={}", name, utf8_percent_encode(value, NON_ALPHANUMERIC)))
+        .collect();
+    let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+    let location = format!("{}{}{}", redirect_uri, separator, query.join("&"));
+
+    match HeaderValue::from_str(&location) {
+        Ok(location) => (StatusCode::FOUND, [(header::LOCATION, location)]).into_response(),
+        Err(_) => (StatusCode::BAD_REQUEST, "Invalid redirect_uri").into_response(),
+    }
+}
+
+fn token_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (status, Json(json!({ "error": error, "error_description": description }))).into_response()
+}
+
+/// A bare-bones login page that posts the authorization request back with the user's credentials.
+fn login_form(params: &Map<String, Value>, error: Option<&str>) -> Response {
+    let hidden: String = params
+        .iter()
+        .filter(|(name, _)| !matches!(name.as_str(), "username" | "password"))
+        .filter_map(|(name, value)| {
+            let value = value.as_str()?;
+            Some(format!("<input type=\"hidden\" name=\"{}\" value=\"{}\">", escape_html(name), escape_html(value)))
+        })
+        .collect();
+    let error = error.map(|e| format!("<p style=\"color:#b00\">{}</p>", escape_html(e))).unwrap_or_default();
+
+    let page = format!(
+        "<!doctype html><html><head><title>Sign in</title></head><body>\
+         <h1>Sign in</h1>{error}\
+         <form method=\"post\" action=\"{action}\">{hidden}\
+         <p><label>Username <input name=\"username\" autofocus></label></p>\
+         <p><label>Password <input name=\"password\" type=\"password\"></label></p>\
+         <button type=\"submit\">Sign in</button></form></body></html>",
+        action = AUTHORIZE_PATH,
+    );
+    let status = if error.is_empty() { StatusCode::OK } else { StatusCode::UNAUTHORIZED };
+    (status, Html(page)).into_response()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
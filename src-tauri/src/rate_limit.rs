@@ -0,0 +1,244 @@
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::models::{ApiKeyLocation, AuthConfig, RateLimit, RateLimitAlgorithm, RateLimitKey};
+
+/// Where a mock server's clients stand against their rate limits, as shown in the app.
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitCounter {
+    /// The route with its own limit, or `None` for the collection's shared one.
+    pub route_id: Option<String>,
+    /// The IP address, header value or API key the client was told apart by.
+    pub key: String,
+    pub algorithm: RateLimitAlgorithm,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the window resets or the bucket is full again.
+    pub reset_in_secs: u64,
+}
+
+/// The outcome of counting one request against a limit.
+pub struct Decision {
+    pub allowed: bool,
+    limit: u32,
+    remaining: u32,
+    reset_in_secs: u64,
+    retry_after_secs: u64,
+}
+
+/// A route ID (`None` for the collection's shared limit) and client key.
+type CounterKey = (Option<String>, String);
+
+struct Counter {
+    config: RateLimit,
+    state: CounterState,
+}
+
+enum CounterState {
+    Window { started: Instant, count: u32 },
+    Bucket { tokens: f64, updated: Instant },
+}
+
+/// Request counts per route (or collection) and client. Held in memory, so a restarted
+/// server starts every client afresh.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    counters: Arc<Mutex<HashMap<CounterKey, Counter>>>,
+}
+
+impl RateLimiter {
+    /// Counts a request from `key` against `config`, scoped to a route or, when
+    /// `route_id` is `None`, to the whole collection.
+    pub fn acquire(&self, route_id: Option<&str>, key: String, config: &RateLimit) -> Decision {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters
+            .entry((route_id.map(str::to_string), key))
+            .or_insert_with(|| Counter::new(config, now));
+        // Start over when the limit was edited while the server ran
+        if counter.config.algorithm != config.algorithm
+            || counter.config.limit != config.limit
+            || counter.config.window_secs != config.window_secs
+        {
+            *counter = Counter::new(config, now);
+        }
+        counter.acquire(now)
+    }
+
+    pub fn counters(&self) -> Vec<RateLimitCounter> {
+        let now = Instant::now();
+        self.counters
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .map(|((route_id, key), counter)| {
+                counter.refresh(now);
+                let (remaining, reset_in_secs) = counter.remaining(now);
+                RateLimitCounter {
+                    route_id: route_id.clone(),
+                    key: key.clone(),
+                    algorithm: counter.config.algorithm,
+                    limit: counter.config.limit,
+                    remaining,
+                    reset_in_secs,
+                }
+            })
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.counters.lock().unwrap().clear();
+    }
+}
+
+impl Counter {
+    fn new(config: &RateLimit, now: Instant) -> Self {
+        let state = match config.algorithm {
+            RateLimitAlgorithm::FixedWindow => CounterState::Window { started: now, count: 0 },
+            RateLimitAlgorithm::TokenBucket => CounterState::Bucket { tokens: config.limit as f64, updated: now },
+        };
+        Self { config: config.clone(), state }
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.window_secs)
+    }
+
+    /// Tokens added back per second.
+    fn refill_rate(&self) -> f64 {
+        self.config.limit as f64 / self.config.window_secs as f64
+    }
+
+    /// Starts a new window, or tops the bucket up, for the time that has passed.
+    fn refresh(&mut self, now: Instant) {
+        let window = self.window();
+        let rate = self.refill_rate();
+        let limit = self.config.limit as f64;
+
+        match self.state {
+            CounterState::Window { ref mut started, ref mut count } => {
+                if now.duration_since(*started) >= window {
+                    *started = now;
+                    *count = 0;
+                }
+            }
+            CounterState::Bucket { ref mut tokens, ref mut updated } => {
+                *tokens = (*tokens + now.duration_since(*updated).as_secs_f64() * rate).min(limit);
+                *updated = now;
+            }
+        }
+    }
+
+    fn acquire(&mut self, now: Instant) -> Decision {
+        self.refresh(now);
+
+        let allowed = match self.state {
+            CounterState::Window { ref mut count, .. } if *count < self.config.limit => {
+                *count += 1;
+                true
+            }
+            CounterState::Bucket { ref mut tokens, .. } if *tokens >= 1.0 => {
+                *tokens -= 1.0;
+                true
+            }
+            _ => false,
+        };
+
+        let (remaining, reset_in_secs) = self.remaining(now);
+        let retry_after_secs = match self.state {
+            _ if allowed => 0,
+            CounterState::Bucket { tokens, .. } if self.config.limit > 0 => {
+                ((1.0 - tokens) / self.refill_rate()).ceil() as u64
+            }
+            CounterState::Bucket { .. } => self.config.window_secs,
+            CounterState::Window { .. } => reset_in_secs,
+        };
+
+        Decision {
+            allowed,
+            limit: self.config.limit,
+            remaining,
+            reset_in_secs,
+            retry_after_secs: retry_after_secs.max(1),
+        }
+    }
+
+    /// Requests left, and seconds until the limit is fully restored.
+    fn remaining(&self, now: Instant) -> (u32, u64) {
+        match self.state {
+            CounterState::Window { started, count } => {
+                let reset = self.window().saturating_sub(now.duration_since(started));
+                (self.config.limit.saturating_sub(count), reset.as_secs_f64().ceil() as u64)
+            }
+            CounterState::Bucket { tokens, .. } => {
+                let missing = self.config.limit as f64 - tokens;
+                let reset = if missing > 0.0 { (missing / self.refill_rate()).ceil() as u64 } else { 0 };
+                (tokens.floor() as u32, reset)
+            }
+        }
+    }
+}
+
+/// Rejects limits that can't be enforced.
+pub fn check_config(config: &RateLimit) -> Result<(), String> {
+    if config.window_secs == 0 {
+        return Err("Rate limit window must be at least one second".to_string());
+    }
+    if let RateLimitKey::Header { ref name } = config.key {
+        HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid rate limit header: {}", name))?;
+    }
+    Ok(())
+}
+
+/// The value that tells this request's client apart, per `config.key`. Requests that
+/// lack it share one budget.
+pub fn client_key(
+    config: &RateLimit,
+    auth: Option<&AuthConfig>,
+    headers: &HeaderMap,
+    query: &Map<String, Value>,
+    remote_addr: Option<SocketAddr>,
+) -> String {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+
+    let key = match config.key {
+        RateLimitKey::Ip => remote_addr.map(|addr| addr.ip().to_string()),
+        RateLimitKey::Header { ref name } => header(name),
+        RateLimitKey::ApiKey => match auth {
+            Some(AuthConfig::ApiKey { location: ApiKeyLocation::Header, name, .. }) => header(name),
+            Some(AuthConfig::ApiKey { location: ApiKeyLocation::Query, name, .. }) => {
+                query.get(name).and_then(Value::as_str).map(str::to_string)
+            }
+            _ => header(header::AUTHORIZATION.as_str()),
+        },
+    };
+    key.unwrap_or_default()
+}
+
+/// The 429 sent once a client has used up its limit.
+pub fn too_many_requests(decision: &Decision) -> Response {
+    let body = json!({
+        "error": "Too Many Requests",
+        "message": format!("Rate limit exceeded; retry in {} seconds", decision.retry_after_secs),
+    });
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(decision.retry_after_secs));
+    response
+}
+
+/// Adds the `X-RateLimit-*` headers describing the client's limit to a response.
+pub fn add_headers(response: &mut Response, decision: &Decision) {
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert("x-ratelimit-reset", HeaderValue::from(decision.reset_in_secs));
+}
@@ -0,0 +1,75 @@
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{ConnectInfo, FromRequest, Request},
+    http::{HeaderMap, Method, Uri},
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Map, Value};
+use std::net::SocketAddr;
+
+use crate::cookies;
+use crate::template;
+
+/// Everything a mock server looks at in an incoming request, extracted once so that
+/// route matching, validation, templates and the journal all see the same request.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub method: Method,
+    pub uri: Uri,
+    /// The path as sent, still percent-encoded. `/` for the root.
+    pub path: String,
+    pub query: Map<String, Value>,
+    pub headers: HeaderMap,
+    /// The request's cookies, by name.
+    pub cookies: Map<String, Value>,
+    pub body: Bytes,
+    /// The client's address, when the server was started with connection info.
+    pub remote_addr: Option<SocketAddr>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for RequestContext {
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let remote_addr = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        let headers = request.headers().clone();
+        let body = Bytes::from_request(request, state).await.map_err(IntoResponse::into_response)?;
+
+        Ok(Self {
+            path: uri.path().to_string(),
+            query: uri.query().map(template::parse_urlencoded).unwrap_or_default(),
+            cookies: cookies::parse(&headers),
+            method,
+            uri,
+            headers,
+            body,
+            remote_addr,
+        })
+    }
+}
+
+impl RequestContext {
+    /// The `request` part of a template context. `params` starts empty and is filled
+    /// in with what the matched route's path captured.
+    pub fn template_context(&self) -> Value {
+        let headers: Map<String, Value> = self
+            .headers
+            .iter()
+            .map(|(key, value)| (key.as_str().to_string(), Value::String(value.to_str().unwrap_or("").to_string())))
+            .collect();
+
+        json!({
+            "method": self.method.as_str(),
+            "path": self.path,
+            "query": self.query,
+            "headers": headers,
+            "cookies": self.cookies,
+            "params": {},
+            "remote_addr": self.remote_addr.map(|addr| addr.ip().to_string()),
+        })
+    }
+}
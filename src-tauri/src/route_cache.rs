@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::models::Route;
+
+/// A value and the `updated_at` of the route it was built from.
+type Entry<T> = (DateTime<Utc>, Arc<T>);
+
+/// Values built from routes' configuration, such as a parsed schema or compiled
+/// patterns, by route ID. An entry is rebuilt once its route has been updated, so
+/// requests don't redo the work each time.
+pub struct RouteCache<T> {
+    entries: Arc<Mutex<HashMap<String, Entry<T>>>>,
+}
+
+impl<T> Clone for RouteCache<T> {
+    fn clone(&self) -> Self {
+        Self { entries: self.entries.clone() }
+    }
+}
+
+impl<T> Default for RouteCache<T> {
+    fn default() -> Self {
+        Self { entries: Arc::default() }
+    }
+}
+
+impl<T> RouteCache<T> {
+    /// The value built for `route`, calling `build` when there is none yet or the route
+    /// has changed since. Failures aren't kept.
+    pub fn get(&self, route: &Route, build: impl FnOnce() -> Result<T, String>) -> Result<Arc<T>, String> {
+        if let Some((updated_at, value)) = self.entries.lock().unwrap().get(&route.id) {
+            if *updated_at == route.updated_at {
+                return Ok(value.clone());
+            }
+        }
+
+        // Built without the lock held, so a slow build doesn't hold up other routes
+        let value = Arc::new(build()?);
+        self.entries.lock().unwrap().insert(route.id.clone(), (route.updated_at, value.clone()));
+        Ok(value)
+    }
+}
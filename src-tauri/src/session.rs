@@ -0,0 +1,90 @@
+use axum::http::HeaderValue;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::cookies;
+use crate::models::{SessionConfig, SessionUpdate};
+use crate::template;
+
+/// A client's session, as shown in the app.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: String,
+    pub values: Map<String, Value>,
+}
+
+/// The sessions of a mock server's clients, by session ID. Held in memory, so a
+/// restarted server has forgotten everyone.
+#[derive(Clone, Default)]
+pub struct SessionStore {
+    sessions: Arc<Mutex<HashMap<String, Map<String, Value>>>>,
+}
+
+impl SessionStore {
+    /// The values stored in session `id`; empty when there's no such session.
+    pub fn get(&self, id: Option<&str>) -> Map<String, Value> {
+        id.and_then(|id| self.sessions.lock().unwrap().get(id).cloned()).unwrap_or_default()
+    }
+
+    /// Applies a route's changes to session `id`, rendering string values against
+    /// `context`. Returns the session's values afterwards, along with the `Set-Cookie`
+    /// to send: the ID of a session just started, or an expired cookie for one just
+    /// cleared.
+    pub fn apply(
+        &self,
+        config: &SessionConfig,
+        id: Option<&str>,
+        update: &SessionUpdate,
+        context: &Value,
+    ) -> (Map<String, Value>, Option<HeaderValue>) {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        if update.clear {
+            let Some(id) = id else {
+                return (Map::new(), None);
+            };
+            sessions.remove(id);
+            return (Map::new(), cookies::expire_cookie(&config.cookie_name, "/"));
+        }
+
+        let (id, started) = match id.filter(|id| sessions.contains_key(*id)) {
+            Some(id) => (id.to_string(), false),
+            // Removing values from a session that doesn't exist has nothing to do
+            None if update.set.is_empty() => return (Map::new(), None),
+            None => (Uuid::new_v4().to_string(), true),
+        };
+
+        let values = sessions.entry(id.clone()).or_default();
+        for key in &update.remove {
+            values.remove(key);
+        }
+        for (key, value) in &update.set {
+            let value = match value {
+                Value::String(text) => Value::String(template::render(text, context)),
+                value => value.clone(),
+            };
+            values.insert(key.clone(), value);
+        }
+
+        let cookie = started
+            .then(|| format!("{}={}; Path=/; HttpOnly; SameSite=Lax", config.cookie_name, id))
+            .and_then(|cookie| HeaderValue::from_str(&cookie).ok());
+        (values.clone(), cookie)
+    }
+
+    pub fn sessions(&self) -> Vec<Session> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, values)| Session { id: id.clone(), values: values.clone() })
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.sessions.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,93 @@
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
+
+use crate::models::{SseConfig, SseEvent};
+use crate::template;
+use crate::websocket::{self, WebSocketBroadcast};
+
+/// Plays a route's event script as a Server-Sent Events stream.
+///
+/// Each event waits for its own `delay_ms` before being sent, and its data is rendered
+/// as a template with `request` and `event` (`index`, `iteration`) in scope. The stream
+/// ends after the last event unless the script repeats, and in any case when the server
+/// stops, since graceful shutdown would otherwise wait on it forever.
+pub fn respond(
+    config: SseConfig,
+    request: Value,
+    broadcasts: broadcast::Receiver<WebSocketBroadcast>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let keep_alive = KeepAlive::new()
+        .interval(Duration::from_secs(config.keep_alive_secs.unwrap_or(15)))
+        .text("keep-alive");
+    let config = Arc::new(config);
+
+    let events = stream::unfold((0usize, 0u64), move |(index, iteration)| {
+        let config = config.clone();
+        let request = request.clone();
+
+        async move {
+            let (index, iteration) = if index < config.events.len() {
+                (index, iteration)
+            } else if config.repeat && !config.events.is_empty() {
+                (0, iteration + 1)
+            } else {
+                return None;
+            };
+
+            let event = &config.events[index];
+            if event.delay_ms > 0 {
+                sleep(Duration::from_millis(event.delay_ms)).await;
+            }
+
+            let context = json!({
+                "request": request,
+                "event": { "index": index, "iteration": iteration },
+            });
+
+            Some((Ok(build_event(event, &context)), (index + 1, iteration)))
+        }
+    });
+
+    Sse::new(events.take_until(websocket::shutdown(broadcasts))).keep_alive(keep_alive)
+}
+
+/// Rejects scripts that can't be sent: event names or IDs spanning lines, which the SSE
+/// format has no way to carry, and repeating scripts without a single delay, which
+/// would flood the client as fast as the server can write.
+pub fn check_config(config: &SseConfig) -> Result<(), String> {
+    for event in &config.events {
+        if event.event.as_deref().is_some_and(|name| name.contains(['\r', '\n'])) {
+            return Err(format!("SSE event name must be a single line: {:?}", event.event));
+        }
+        if event.id.as_deref().is_some_and(|id| id.contains(['\r', '\n', '\0'])) {
+            return Err(format!("SSE event ID must be a single line without NUL: {:?}", event.id));
+        }
+    }
+    if config.repeat && config.events.iter().all(|event| event.delay_ms == 0) {
+        return Err("A repeating SSE script needs at least one event with a delay".to_string());
+    }
+    Ok(())
+}
+
+fn build_event(event: &SseEvent, context: &Value) -> Event {
+    // Data is split into `data:` lines on `\n`; a bare `\r` can't be sent at all
+    let data = template::render(&event.data, context).replace("\r\n", "\n").replace('\r', "\n");
+    let mut built = Event::default().data(data);
+
+    if let Some(ref name) = event.event {
+        built = built.event(name);
+    }
+    if let Some(ref id) = event.id {
+        built = built.id(id);
+    }
+    if let Some(retry_ms) = event.retry_ms {
+        built = built.retry(Duration::from_millis(retry_ms));
+    }
+
+    built
+}
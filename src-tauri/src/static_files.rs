@@ -0,0 +1,144 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use percent_encoding::percent_decode_str;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+
+use crate::models::{Collection, StaticMount};
+
+const INDEX_FILE: &str = "index.html";
+
+/// Serves a file from the first static mount whose prefix covers the request path.
+///
+/// Returns `None` when no mount applies or the file doesn't exist, so the caller can
+/// fall through to its usual not-found handling. MIME types, `Last-Modified`, range
+/// requests and index files come from tower-http's `ServeDir`; an `ETag` derived from
+/// the file's size and modification time is added on top.
+pub async fn serve(
+    collection: &Collection,
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Option<Response> {
+    if method != Method::GET && method != Method::HEAD {
+        return None;
+    }
+
+    let (mount, rest) = find_mount(&collection.static_mounts, uri.path())?;
+    let dir = resolve_mount_dir(collection, mount);
+
+    let etag = file_for(&dir, rest).and_then(|file| etag_for(&file));
+    if let Some(ref etag) = etag {
+        if if_none_match(headers, etag) {
+            return Some((StatusCode::NOT_MODIFIED, [(header::ETAG, etag.clone())]).into_response());
+        }
+    }
+
+    let mut request = Request::builder()
+        .method(method.clone())
+        .uri(if rest.is_empty() { "/" } else { rest })
+        .body(Body::empty())
+        .ok()?;
+    *request.headers_mut() = headers.clone();
+
+    let serve_dir = ServeDir::new(&dir).append_index_html_on_directories(true);
+    let response = if mount.spa_fallback {
+        serve_dir
+            .fallback(ServeFile::new(dir.join(INDEX_FILE)))
+            .oneshot(request)
+            .await
+            .ok()?
+            .map(Body::new)
+    } else {
+        serve_dir.oneshot(request).await.ok()?.map(Body::new)
+    };
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return None;
+    }
+
+    let mut response = response.into_response();
+    if response.status().is_success() {
+        if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(&etag).ok()) {
+            response.headers_mut().insert(header::ETAG, etag);
+        }
+    }
+
+    Some(response)
+}
+
+/// Rejects mounts that could never serve anything: prefixes that aren't absolute URL
+/// paths, and directories that don't exist.
+pub fn check_mounts(collection: &Collection) -> Result<(), String> {
+    for mount in &collection.static_mounts {
+        if !mount.prefix.starts_with('/') {
+            return Err(format!("Static mount prefix must start with /: {:?}", mount.prefix));
+        }
+        let dir = resolve_mount_dir(collection, mount);
+        if !dir.is_dir() {
+            return Err(format!("Static mount directory not found: {}", dir.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Picks the mount with the longest matching prefix and returns the path below it.
+fn find_mount<'a>(mounts: &'a [StaticMount], path: &'a str) -> Option<(&'a StaticMount, &'a str)> {
+    mounts
+        .iter()
+        .filter_map(|mount| {
+            let prefix = mount.prefix.trim_end_matches('/');
+            let rest = path.strip_prefix(prefix)?;
+            (rest.is_empty() || rest.starts_with('/')).then_some((mount, rest))
+        })
+        .max_by_key(|(mount, _)| mount.prefix.trim_end_matches('/').len())
+}
+
+fn resolve_mount_dir(collection: &Collection, mount: &StaticMount) -> PathBuf {
+    let dir = PathBuf::from(&mount.dir);
+    match &collection.asset_dir {
+        Some(asset_dir) if dir.is_relative() => Path::new(asset_dir).join(dir),
+        _ => dir,
+    }
+}
+
+/// Maps the request path onto the file `ServeDir` will serve, for ETag purposes.
+fn file_for(dir: &Path, rest: &str) -> Option<PathBuf> {
+    let decoded = percent_decode_str(rest).decode_utf8().ok()?;
+    let relative = PathBuf::from(decoded.trim_start_matches('/'));
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+
+    let file = dir.join(relative);
+    if file.is_dir() {
+        Some(file.join(INDEX_FILE))
+    } else {
+        Some(file)
+    }
+}
+
+fn etag_for(file: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(file).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("\"{:x}-{:x}\"", metadata.len(), modified.as_nanos()))
+}
+
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
@@ -0,0 +1,157 @@
+use chrono::Utc;
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+
+use crate::fake::Faker;
+
+/// The most times a `{{#repeat}}` block renders.
+const MAX_REPEAT: i64 = 1000;
+
+/// Rendering stops once output reaches this size, so nested repeats can't exhaust memory.
+const MAX_OUTPUT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Expands `{{ expression }}` placeholders in `template`.
+///
+/// An expression is either a dotted path into `context` (for example
+/// `request.headers.user-agent` or `event.index`) or one of the built-in helpers:
+/// `now`, `timestamp`, `uuid` and the `fake.*` data generators (`fake.email`,
+/// `fake.int 1 10`, ...). Strings are inserted as-is, other values as JSON,
+/// and anything that doesn't resolve renders as an empty string.
+///
+/// `{{#repeat N}}...{{/repeat}}` (or `{{#repeat MIN MAX}}`) renders its contents N
+/// times, separated by commas, with `repeat.index` in scope, which makes arrays
+/// like `[{{#repeat 3}}{"id": "{{ fake.uuid }}"}{{/repeat}}]` easy to write. Counts
+/// are capped at `MAX_REPEAT`, and output stops growing past `MAX_OUTPUT_BYTES`.
+pub fn render(template: &str, context: &Value) -> String {
+    render_with(template, context, &mut Faker::new(None))
+}
+
+/// Like `render`, drawing generated values from `faker` so a seeded faker gives
+/// the same output every time.
+pub fn render_with(template: &str, context: &Value, faker: &mut Faker) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        if output.len() >= MAX_OUTPUT_BYTES {
+            return output;
+        }
+
+        output.push_str(&rest[..start]);
+        let expression = rest[start + 2..start + 2 + end].trim();
+        rest = &rest[start + 2 + end + 2..];
+
+        if let Some(args) = expression.strip_prefix("#repeat") {
+            let (block, after) = split_block(rest);
+            output.push_str(&repeat(args, block, context, faker));
+            rest = after;
+        } else {
+            output.push_str(&evaluate(expression, context, faker));
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Splits the text following a `{{#repeat}}` tag at its matching `{{/repeat}}`.
+fn split_block(rest: &str) -> (&str, &str) {
+    let mut depth = 0;
+    let mut offset = 0;
+
+    while let Some(start) = rest[offset..].find("{{").map(|start| offset + start) {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let tag = rest[start + 2..start + 2 + end].trim();
+        offset = start + 2 + end + 2;
+
+        if tag.starts_with("#repeat") {
+            depth += 1;
+        } else if tag == "/repeat" {
+            if depth == 0 {
+                return (&rest[..start], &rest[offset..]);
+            }
+            depth -= 1;
+        }
+    }
+
+    (rest, "")
+}
+
+fn repeat(args: &str, block: &str, context: &Value, faker: &mut Faker) -> String {
+    let bounds: Vec<i64> = args.split_whitespace().filter_map(|arg| arg.parse().ok()).collect();
+    let count = match bounds[..] {
+        [count] => count,
+        [min, max] => faker.int(min, max),
+        _ => 0,
+    };
+
+    let count = count.clamp(0, MAX_REPEAT);
+
+    let mut context = context.clone();
+    let mut items = Vec::new();
+    let mut size = 0;
+    for index in 0..count {
+        if size >= MAX_OUTPUT_BYTES {
+            break;
+        }
+        if let Value::Object(ref mut map) = context {
+            map.insert("repeat".to_string(), json!({ "index": index, "count": count }));
+        }
+        let item = render_with(block, &context, faker);
+        size += item.len() + 1;
+        items.push(item);
+    }
+    items.join(",")
+}
+
+fn evaluate(expression: &str, context: &Value, faker: &mut Faker) -> String {
+    match expression {
+        "now" => return Utc::now().to_rfc3339(),
+        "timestamp" => return Utc::now().timestamp_millis().to_string(),
+        "uuid" => return faker.uuid(),
+        _ => {}
+    }
+
+    if let Some(helper) = expression.strip_prefix("fake.") {
+        let mut parts = helper.split_whitespace();
+        let name = parts.next().unwrap_or_default();
+        let args: Vec<&str> = parts.collect();
+        return faker.helper(name, &args).unwrap_or_default();
+    }
+
+    match lookup(context, expression) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(context, |value, key| match value {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+/// Decodes `application/x-www-form-urlencoded` text, as found in query strings and
+/// form bodies, into an object of string values. Later duplicates win.
+pub fn parse_urlencoded(input: &str) -> serde_json::Map<String, Value> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_query_component(key), Value::String(decode_query_component(value)))
+        })
+        .collect()
+}
+
+fn decode_query_component(component: &str) -> String {
+    percent_decode_str(&component.replace('+', " ")).decode_utf8_lossy().into_owned()
+}
@@ -0,0 +1,94 @@
+use axum::{
+    body::Bytes,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::models::RequestValidation;
+
+/// One reason a request was rejected.
+#[derive(Debug, Serialize)]
+pub struct ValidationError {
+    /// Which part of the request failed: `body`, `header` or `query`.
+    pub location: &'static str,
+    /// JSON Pointer into the body, or the header or parameter name.
+    pub path: String,
+    pub message: String,
+}
+
+/// Compiles a JSON Schema, describing what's wrong with it when it isn't valid.
+pub fn compile_schema(schema: &Value) -> Result<Validator, String> {
+    jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))
+}
+
+/// Checks a request against the route's validation rules and lists every problem found.
+/// `request` is the template request context, which carries the headers and query, and
+/// `body_validator` is the rules' `body_schema` compiled.
+pub fn validate(
+    config: &RequestValidation,
+    body_validator: Option<&Validator>,
+    request: &Value,
+    body: &Bytes,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    for name in &config.required_headers {
+        if request["headers"].get(name.to_ascii_lowercase()).is_none() {
+            errors.push(ValidationError {
+                location: "header",
+                path: name.clone(),
+                message: format!("Missing required header {}", name),
+            });
+        }
+    }
+
+    for name in &config.required_query_params {
+        if request["query"].get(name).is_none() {
+            errors.push(ValidationError {
+                location: "query",
+                path: name.clone(),
+                message: format!("Missing required query parameter {}", name),
+            });
+        }
+    }
+
+    if let Some(validator) = body_validator {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(instance) => {
+                errors.extend(validator.iter_errors(&instance).map(|error| ValidationError {
+                    location: "body",
+                    path: error.instance_path.to_string(),
+                    message: error.to_string(),
+                }));
+            }
+            Err(e) => errors.push(ValidationError {
+                location: "body",
+                path: String::new(),
+                message: format!("Body is not valid JSON: {}", e),
+            }),
+        }
+    }
+
+    errors
+}
+
+/// The response sent in place of the route's own when validation fails.
+pub fn error_response(config: &RequestValidation, errors: Vec<ValidationError>) -> Response {
+    let status = config
+        .status_code
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::BAD_REQUEST);
+
+    (
+        status,
+        Json(json!({
+            "error": "Request validation failed",
+            "errors": errors,
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,195 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use futures_util::{SinkExt, StreamExt};
+use log::warn;
+use regex::Regex;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval, Duration};
+
+use crate::matching::json_contains;
+use crate::models::{MessageMatcher, WebSocketConfig};
+use crate::template;
+
+/// Signals fanned out from a `MockServer` to all of its WebSocket connections and
+/// SSE streams.
+#[derive(Debug, Clone)]
+pub enum WebSocketBroadcast {
+    /// An ad-hoc frame pushed from the app, for clients of one route path or of every route.
+    Message { path: Option<String>, message: String },
+    /// The server is stopping; connections and streams should close.
+    Shutdown,
+}
+
+/// Resolves once the server sends `Shutdown` or goes away.
+pub async fn shutdown(mut broadcasts: broadcast::Receiver<WebSocketBroadcast>) {
+    loop {
+        match broadcasts.recv().await {
+            Ok(WebSocketBroadcast::Shutdown) | Err(broadcast::error::RecvError::Closed) => break,
+            Ok(WebSocketBroadcast::Message { .. }) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+        }
+    }
+}
+
+/// Upgrades the connection and runs the route's scripted behavior on it until the
+/// client disconnects.
+pub fn respond(
+    upgrade: WebSocketUpgrade,
+    config: WebSocketConfig,
+    path: String,
+    request: Value,
+    broadcasts: broadcast::Sender<WebSocketBroadcast>,
+) -> Response {
+    let broadcasts = broadcasts.subscribe();
+    upgrade.on_upgrade(move |socket| run(socket, config, path, request, broadcasts))
+}
+
+async fn run(
+    socket: WebSocket,
+    config: WebSocketConfig,
+    path: String,
+    request: Value,
+    mut broadcasts: broadcast::Receiver<WebSocketBroadcast>,
+) {
+    let (mut sink, mut stream) = socket.split();
+
+    // Everything headed to the client goes through one channel so the sink has a single writer
+    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let patterns = compile_rules(&config);
+
+    let context = json!({ "request": request });
+    for frame in &config.on_connect {
+        let _ = tx.send(Message::Text(template::render(frame, &context)));
+    }
+
+    let mut tasks = Vec::new();
+
+    for push in config.periodic.iter().filter(|push| push.interval_ms > 0) {
+        let tx = tx.clone();
+        let push = push.clone();
+        let request = request.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_millis(push.interval_ms));
+            ticker.tick().await;
+            for tick in 0u64.. {
+                ticker.tick().await;
+                let context = json!({ "request": request, "tick": tick });
+                if tx.send(Message::Text(template::render(&push.message, &context))).is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    // Forwards pushed frames until the server shuts down
+    let broadcast_tx = tx.clone();
+    let mut forwarder = tokio::spawn(async move {
+        loop {
+            match broadcasts.recv().await {
+                Ok(WebSocketBroadcast::Message { path: target, message }) => {
+                    if target.as_deref().is_none_or(|p| p == path)
+                        && broadcast_tx.send(Message::Text(message)).is_err()
+                    {
+                        break;
+                    }
+                }
+                Ok(WebSocketBroadcast::Shutdown) | Err(broadcast::error::RecvError::Closed) => break,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            incoming = stream.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    for reply in reply_to(&config, &patterns, &text, &request) {
+                        let _ = tx.send(Message::Text(reply));
+                    }
+                }
+                Some(Ok(Message::Binary(data))) if config.echo => {
+                    let _ = tx.send(Message::Binary(data));
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = &mut forwarder => {
+                let _ = tx.send(Message::Close(None));
+                break;
+            }
+        }
+    }
+
+    forwarder.abort();
+    for task in tasks {
+        task.abort();
+    }
+    drop(tx);
+    let _ = writer.await;
+}
+
+/// Rejects rules with regular expressions that don't compile.
+pub fn check_config(config: &WebSocketConfig) -> Result<(), String> {
+    for rule in &config.rules {
+        if let MessageMatcher::Regex(ref pattern) = rule.matcher {
+            Regex::new(pattern).map_err(|e| format!("Invalid message pattern {}: {}", pattern, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Each rule's regex, compiled once per connection; `None` for rules that don't use
+/// one, or whose pattern is invalid and so never matches.
+fn compile_rules(config: &WebSocketConfig) -> Vec<Option<Regex>> {
+    config
+        .rules
+        .iter()
+        .map(|rule| match rule.matcher {
+            MessageMatcher::Regex(ref pattern) => Regex::new(pattern)
+                .map_err(|e| warn!("Invalid WebSocket message pattern {}: {}", pattern, e))
+                .ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Renders the replies of the first rule matching `text`, or echoes it back when
+/// echo mode is on and nothing matched. `patterns` are the rules' compiled regexes.
+fn reply_to(config: &WebSocketConfig, patterns: &[Option<Regex>], text: &str, request: &Value) -> Vec<String> {
+    let parsed = serde_json::from_str::<Value>(text).ok();
+
+    let matched = config
+        .rules
+        .iter()
+        .zip(patterns)
+        .find(|(rule, regex)| message_matches(&rule.matcher, regex.as_ref(), text, parsed.as_ref()));
+    match matched.map(|(rule, _)| rule) {
+        Some(rule) => {
+            let context = json!({
+                "request": request,
+                "message": { "text": text, "json": parsed },
+            });
+            rule.replies.iter().map(|reply| template::render(reply, &context)).collect()
+        }
+        None if config.echo => vec![text.to_string()],
+        None => Vec::new(),
+    }
+}
+
+fn message_matches(matcher: &MessageMatcher, regex: Option<&Regex>, text: &str, parsed: Option<&Value>) -> bool {
+    match matcher {
+        MessageMatcher::Exact(expected) => text == expected,
+        MessageMatcher::Contains(needle) => text.contains(needle.as_str()),
+        MessageMatcher::Regex(_) => regex.is_some_and(|re| re.is_match(text)),
+        MessageMatcher::JsonPartial(expected) => parsed.is_some_and(|actual| json_contains(actual, expected)),
+        MessageMatcher::Any => true,
+    }
+}